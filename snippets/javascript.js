@@ -0,0 +1,48 @@
+function fibonacci(n) {
+  if (n < 2) {
+    return n;
+  }
+  return fibonacci(n - 1) + fibonacci(n - 2);
+}
+
+const countWords = (text) => {
+  const counts = {};
+  for (const word of text.split(/\s+/)) {
+    const key = word.toLowerCase();
+    counts[key] = (counts[key] || 0) + 1;
+  }
+  return counts;
+};
+
+class Stack {
+  constructor() {
+    this.items = [];
+  }
+
+  push(item) {
+    this.items.push(item);
+  }
+
+  pop() {
+    if (this.items.length === 0) {
+      throw new Error("pop from empty stack");
+    }
+    return this.items.pop();
+  }
+}
+
+async function fetchJson(url) {
+  const response = await fetch(url);
+  if (!response.ok) {
+    throw new Error(`request failed with status ${response.status}`);
+  }
+  return response.json();
+}
+
+function debounce(callback, delay) {
+  let timer = null;
+  return (...args) => {
+    clearTimeout(timer);
+    timer = setTimeout(() => callback(...args), delay);
+  };
+}
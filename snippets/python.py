@@ -0,0 +1,45 @@
+def fibonacci(n):
+    if n < 2:
+        return n
+    return fibonacci(n - 1) + fibonacci(n - 2)
+
+class Stack:
+    def __init__(self):
+        self.items = []
+
+    def push(self, item):
+        self.items.append(item)
+
+    def pop(self):
+        if not self.items:
+            raise IndexError("pop from empty stack")
+        return self.items.pop()
+
+def count_words(text):
+    counts = {}
+    for word in text.split():
+        word = word.lower()
+        counts[word] = counts.get(word, 0) + 1
+    return counts
+
+def read_config(path):
+    config = {}
+    with open(path, "r") as file:
+        for line in file:
+            if line.startswith("#") or "=" not in line:
+                continue
+            key, value = line.split("=", 1)
+            config[key.strip()] = value.strip()
+    return config
+
+def binary_search(items, target):
+    low, high = 0, len(items) - 1
+    while low <= high:
+        middle = (low + high) // 2
+        if items[middle] == target:
+            return middle
+        elif items[middle] < target:
+            low = middle + 1
+        else:
+            high = middle - 1
+    return None
@@ -0,0 +1,253 @@
+use std::{
+    fs::{canonicalize, read_to_string},
+    io::{self, ErrorKind},
+};
+
+use itertools::Itertools;
+
+use crate::{
+    custom_text::split_chunks,
+    history::HistoryAverages,
+    storage::{read_key_values, write_key_values},
+};
+
+const BOOKMARKS_FILE: &str = "bookmarks.tsv";
+pub const DEFAULT_WORDS_PER_TEST: usize = 50;
+
+/// A long text typed test by test over several runs
+#[derive(Debug, PartialEq)]
+pub struct Book {
+    /// Identifies the book between runs, so its bookmark can be found
+    pub key: String,
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    /// Text of every test of the chapter
+    pub tests: Vec<String>,
+}
+
+/// Position of the next test to type in a book
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Bookmark {
+    pub chapter: usize,
+    pub test: usize,
+}
+
+/// Progress through a book, shown after every test
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BookProgress {
+    pub chapter: String,
+    /// Number of the test just typed, starting at 1
+    pub test: usize,
+    pub total_tests: usize,
+    /// Averages of every test typed on this chapter
+    pub chapter_averages: HistoryAverages,
+}
+
+impl Book {
+    pub fn load(path: &str, words_per_test: Option<usize>) -> io::Result<Self> {
+        let key = canonicalize(path)?.to_string_lossy().to_string();
+        let chapters = split_chapters(
+            &read_to_string(path)?,
+            words_per_test.unwrap_or(DEFAULT_WORDS_PER_TEST),
+        );
+        if chapters.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("`{}` doesn't contain any words", path),
+            ));
+        }
+
+        Ok(Self { key, chapters })
+    }
+
+    /// Bookmark saved by the last run on this book, or its beginning
+    pub fn saved_bookmark(&self) -> io::Result<Bookmark> {
+        let bookmarks = read_key_values(BOOKMARKS_FILE)?;
+        let bookmark = bookmarks
+            .get(&self.key)
+            .and_then(|bookmark| bookmark.split_once(' '))
+            .and_then(|(chapter, test)| {
+                Some(Bookmark {
+                    chapter: chapter.parse().ok()?,
+                    test: test.parse().ok()?,
+                })
+            })
+            .filter(|bookmark| self.test(bookmark).is_some())
+            .unwrap_or_default();
+
+        Ok(bookmark)
+    }
+
+    pub fn save_bookmark(&self, bookmark: &Bookmark) -> io::Result<()> {
+        let mut bookmarks = read_key_values(BOOKMARKS_FILE)?;
+        bookmarks.insert(
+            self.key.clone(),
+            format!("{} {}", bookmark.chapter, bookmark.test),
+        );
+        write_key_values(BOOKMARKS_FILE, &bookmarks)
+    }
+
+    pub fn test(&self, bookmark: &Bookmark) -> Option<&String> {
+        self.chapters
+            .get(bookmark.chapter)?
+            .tests
+            .get(bookmark.test)
+    }
+
+    /// Bookmark of the test following `bookmark`. The book starts over after its last test
+    pub fn next(&self, bookmark: &Bookmark) -> Bookmark {
+        let next_test = Bookmark {
+            chapter: bookmark.chapter,
+            test: bookmark.test + 1,
+        };
+        let next_chapter = Bookmark {
+            chapter: bookmark.chapter + 1,
+            test: 0,
+        };
+
+        [next_test, next_chapter]
+            .into_iter()
+            .find(|bookmark| self.test(bookmark).is_some())
+            .unwrap_or_default()
+    }
+}
+
+/// Split a text in chapters, each of them split in tests of `words_per_test` words
+///
+/// A chapter starts on a heading: a line starting with `chapter` (in any case) or a
+/// Markdown `#` heading. The text before the first heading is a chapter on its own.
+/// Chapters without words are skipped
+pub fn split_chapters(text: &str, words_per_test: usize) -> Vec<Chapter> {
+    let mut chapters = vec![];
+    let mut title = "Beginning".to_string();
+    let mut lines = vec![];
+
+    for line in text.lines() {
+        if is_chapter_heading(line) {
+            chapters.push((title, lines.join("\n")));
+            title = line.trim().trim_start_matches('#').trim().to_string();
+            lines.clear();
+        } else {
+            lines.push(line);
+        }
+    }
+    chapters.push((title, lines.join("\n")));
+
+    chapters
+        .into_iter()
+        .map(|(title, text)| Chapter {
+            title,
+            tests: split_chunks(&text, Some(words_per_test)),
+        })
+        .filter(|chapter| !chapter.tests.is_empty())
+        .collect_vec()
+}
+
+fn is_chapter_heading(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('#')
+        || line
+            .get(..8)
+            .is_some_and(|start| start.eq_ignore_ascii_case("chapter "))
+}
+
+#[cfg(test)]
+mod split_chapters_tests {
+    use super::{split_chapters, Chapter};
+
+    #[test]
+    fn chapters_are_split_on_headings() {
+        let text = "A Tale\n\nCHAPTER I\none two three\n\n# Chapter II\nfour";
+
+        let result = split_chapters(text, 2);
+
+        assert_eq!(
+            result,
+            vec![
+                Chapter {
+                    title: "Beginning".to_string(),
+                    tests: vec!["A Tale".to_string()],
+                },
+                Chapter {
+                    title: "CHAPTER I".to_string(),
+                    tests: vec!["one two".to_string(), "three".to_string()],
+                },
+                Chapter {
+                    title: "Chapter II".to_string(),
+                    tests: vec!["four".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_chapters_are_skipped() {
+        let result = split_chapters("Chapter 1\n\nChapter 2\nword", 2);
+
+        assert_eq!(
+            result,
+            vec![Chapter {
+                title: "Chapter 2".to_string(),
+                tests: vec!["word".to_string()],
+            }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod next_bookmark_tests {
+    use super::{split_chapters, Book, Bookmark};
+
+    fn book() -> Book {
+        Book {
+            key: "book".to_string(),
+            chapters: split_chapters("Chapter 1\na b c\nChapter 2\nd", 2),
+        }
+    }
+
+    #[test]
+    fn next_test_of_the_chapter() {
+        let result = book().next(&Bookmark {
+            chapter: 0,
+            test: 0,
+        });
+
+        assert_eq!(
+            result,
+            Bookmark {
+                chapter: 0,
+                test: 1
+            }
+        );
+    }
+
+    #[test]
+    fn next_chapter() {
+        let result = book().next(&Bookmark {
+            chapter: 0,
+            test: 1,
+        });
+
+        assert_eq!(
+            result,
+            Bookmark {
+                chapter: 1,
+                test: 0
+            }
+        );
+    }
+
+    #[test]
+    fn book_starts_over() {
+        let result = book().next(&Bookmark {
+            chapter: 1,
+            test: 0,
+        });
+
+        assert_eq!(result, Bookmark::default());
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    fs::read_to_string,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use itertools::Itertools;
+use rand::Rng;
+
+/// Programming languages with bundled snippets and syntax-aware coloring
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    /// Source code whose language couldn't be detected. It's rendered without coloring
+    Plain,
+}
+
+/// Kind of token a character of a snippet belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Keyword,
+    String,
+    Comment,
+    Number,
+    Punctuation,
+    Plain,
+}
+
+impl Language {
+    /// Find a language by its name or one of its usual file extensions
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Some(Self::Rust),
+            "python" | "py" => Some(Self::Python),
+            "javascript" | "js" | "typescript" | "ts" => Some(Self::JavaScript),
+            "go" | "golang" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    /// Snippets shipped within the binary for this language
+    fn bundled_snippets(&self) -> &'static str {
+        match self {
+            Self::Rust => include_str!("../snippets/rust.rs"),
+            Self::Python => include_str!("../snippets/python.py"),
+            Self::JavaScript => include_str!("../snippets/javascript.js"),
+            Self::Go => include_str!("../snippets/go.go"),
+            Self::Plain => "",
+        }
+    }
+
+    /// Keywords of the language separated by spaces
+    fn keywords(&self) -> &'static str {
+        match self {
+            Self::Rust => concat!(
+                "as async await break const continue crate else enum false fn for if impl in let ",
+                "loop match mod move mut pub ref return self Self static struct super trait true ",
+                "type unsafe use where while",
+            ),
+            Self::Python => concat!(
+                "and as assert async await break class continue def del elif else except False ",
+                "finally for from if import in is lambda None not or pass raise return self True ",
+                "try while with yield",
+            ),
+            Self::JavaScript => concat!(
+                "async await break case catch class const constructor continue default else ",
+                "export false for function if import in let new null of return switch this throw ",
+                "true try typeof undefined var while",
+            ),
+            Self::Go => concat!(
+                "break case chan const continue default defer else false for func go if import ",
+                "interface map nil package range return select struct switch true type var",
+            ),
+            Self::Plain => "",
+        }
+    }
+
+    fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Self::Rust | Self::JavaScript | Self::Go => Some("//"),
+            Self::Python => Some("#"),
+            Self::Plain => None,
+        }
+    }
+
+    fn string_delimiters(&self) -> &'static [char] {
+        match self {
+            Self::Rust => &['"'],
+            Self::Python => &['"', '\''],
+            Self::JavaScript => &['"', '\'', '`'],
+            Self::Go => &['"', '`'],
+            Self::Plain => &[],
+        }
+    }
+}
+
+/// Source code to be typed along with the language it's written in
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSnippet {
+    pub code: String,
+    pub language: Language,
+}
+
+/// Load a random snippet from `source`
+///
+/// `source` can be either the path to a source file, whose language is guessed by its
+/// extension, or the name of a language with bundled snippets
+pub fn load_snippet(source: &str) -> io::Result<CodeSnippet> {
+    let path = Path::new(source);
+    let (content, language) = if path.is_file() {
+        let language = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Language::from_name)
+            .unwrap_or(Language::Plain);
+        (read_to_string(path)?, language)
+    } else if let Some(language) = Language::from_name(source) {
+        (language.bundled_snippets().to_string(), language)
+    } else {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("`{}` is neither a source file nor a known language", source),
+        ));
+    };
+
+    let snippets = split_snippets(&content);
+    if snippets.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("`{}` doesn't contain any code", source),
+        ));
+    }
+
+    let code = snippets[rand::thread_rng().gen_range(0..snippets.len())].clone();
+    Ok(CodeSnippet { code, language })
+}
+
+/// Split source code into top level blocks
+///
+/// A block ends on a blank line followed by a non indented line, so blank lines inside a
+/// function body don't break it. Every block is normalized with [normalize_snippet]
+pub fn split_snippets(source: &str) -> Vec<String> {
+    let lines = source.lines().collect_vec();
+    let mut snippets = vec![];
+    let mut current = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        let next_is_top_level = lines
+            .get(index + 1)
+            .is_some_and(|next| !next.starts_with(char::is_whitespace));
+        if line.trim().is_empty() && next_is_top_level {
+            snippets.push(current.join("\n"));
+            current.clear();
+        } else {
+            current.push(*line);
+        }
+    }
+    snippets.push(current.join("\n"));
+
+    snippets
+        .iter()
+        .map(|snippet| normalize_snippet(snippet))
+        .filter(|snippet| !snippet.is_empty())
+        .collect()
+}
+
+/// Remove the indentation shared by every line and trailing whitespace. Tabs are kept, as
+/// they are typed with the Tab key
+pub fn normalize_snippet(snippet: &str) -> String {
+    let lines = snippet.lines().map(str::trim_end).collect_vec();
+    let indentation = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|shared, indentation| {
+            let length = shared
+                .chars()
+                .zip(indentation.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            &shared[..length]
+        })
+        .unwrap_or("");
+
+    lines
+        .iter()
+        .map(|line| line.strip_prefix(indentation).unwrap_or(""))
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+/// Classify every character of `code`. The result has one entry per character
+pub fn highlight(code: &str, language: Language) -> Vec<TokenKind> {
+    let chars = code.chars().collect_vec();
+    let mut kinds = Vec::with_capacity(chars.len());
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let rest: String = chars[index..].iter().take(2).collect();
+        let (kind, length) = if language
+            .line_comment()
+            .is_some_and(|comment| rest.starts_with(comment))
+        {
+            let length = chars[index..].iter().take_while(|&&c| c != '\n').count();
+            (TokenKind::Comment, length)
+        } else if language.string_delimiters().contains(&c) {
+            let mut length = 1;
+            while index + length < chars.len() && chars[index + length] != c {
+                if chars[index + length] == '\\' {
+                    length += 1;
+                }
+                length += 1;
+            }
+            (TokenKind::String, (length + 1).min(chars.len() - index))
+        } else if c.is_ascii_digit() {
+            let length = chars[index..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphanumeric() || **c == '.' || **c == '_')
+                .count();
+            (TokenKind::Number, length)
+        } else if c.is_alphabetic() || c == '_' {
+            let word: String = chars[index..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric() || **c == '_')
+                .collect();
+            let kind = if language
+                .keywords()
+                .split(' ')
+                .any(|keyword| keyword == word)
+            {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Plain
+            };
+            (kind, word.chars().count())
+        } else if c.is_ascii_punctuation() {
+            (TokenKind::Punctuation, 1)
+        } else {
+            (TokenKind::Plain, 1)
+        };
+
+        kinds.extend(std::iter::repeat_n(kind, length));
+        index += length;
+    }
+
+    kinds
+}
+
+#[cfg(test)]
+mod split_snippets_tests {
+    use super::split_snippets;
+
+    #[test]
+    fn blocks_are_split_on_top_level_blank_lines() {
+        let source = "fn a() {\n    one();\n\n    two();\n}\n\nfn b() {}\n";
+
+        let result = split_snippets(source);
+
+        assert_eq!(
+            result,
+            vec![
+                "fn a() {\n    one();\n\n    two();\n}".to_string(),
+                "fn b() {}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_source() {
+        let result = split_snippets("\n\n");
+
+        assert!(result.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod normalize_snippet_tests {
+    use super::normalize_snippet;
+
+    #[test]
+    fn shared_indentation_is_removed() {
+        let result = normalize_snippet("    if a {\n        b()\n    }");
+
+        assert_eq!(result, "if a {\n    b()\n}");
+    }
+
+    #[test]
+    fn tabs_are_kept_and_trailing_spaces_removed() {
+        let result = normalize_snippet("\tif a {  \n\t\tb()\n\t}\n");
+
+        assert_eq!(result, "if a {\n\tb()\n}");
+    }
+}
+
+#[cfg(test)]
+mod highlight_tests {
+    use super::{highlight, Language, TokenKind};
+
+    #[test]
+    fn keywords_strings_and_comments() {
+        let result = highlight("let a = \"b\"; // c", Language::Rust);
+
+        assert_eq!(result.len(), 17);
+        assert_eq!(result[..3], [TokenKind::Keyword; 3]);
+        assert_eq!(result[3..6], [TokenKind::Plain; 3]);
+        assert_eq!(result[6], TokenKind::Punctuation);
+        assert_eq!(result[8..11], [TokenKind::String; 3]);
+        assert_eq!(result[13..], [TokenKind::Comment; 4]);
+    }
+
+    #[test]
+    fn plain_language_has_no_keywords() {
+        let result = highlight("let 1", Language::Plain);
+
+        assert_eq!(
+            result,
+            vec![
+                TokenKind::Plain,
+                TokenKind::Plain,
+                TokenKind::Plain,
+                TokenKind::Plain,
+                TokenKind::Number
+            ]
+        );
+    }
+}
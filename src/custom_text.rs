@@ -0,0 +1,122 @@
+use std::{
+    fs::{canonicalize, read_to_string},
+    io::{self, stdin, ErrorKind, Read},
+};
+
+use itertools::Itertools;
+
+use crate::storage::{read_key_values, write_key_values};
+
+const POSITIONS_FILE: &str = "positions.tsv";
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Text provided by the user, split in chunks that are typed one test at a time
+#[derive(Debug, PartialEq)]
+pub struct CustomText {
+    /// Identifies the text between runs, so the position can be resumed
+    pub key: String,
+    pub chunks: Vec<String>,
+}
+
+impl CustomText {
+    /// Load the text from `source`, which is either a file or `-` to read the standard input
+    ///
+    /// The text is split in chunks of `words_per_chunk` words, or left as a single chunk
+    /// when it's not provided
+    pub fn load(source: &str, words_per_chunk: Option<usize>) -> io::Result<Self> {
+        let (key, content) = if source == "-" {
+            let mut content = String::new();
+            stdin().read_to_string(&mut content)?;
+            (format!("stdin:{:x}", fnv1a(&content)), content)
+        } else {
+            let key = canonicalize(source)?.to_string_lossy().to_string();
+            (key, read_to_string(source)?)
+        };
+
+        let chunks = split_chunks(&content, words_per_chunk);
+        if chunks.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("`{}` doesn't contain any words", source),
+            ));
+        }
+
+        Ok(Self { key, chunks })
+    }
+
+    /// Index of the chunk where the last run on this text stopped
+    pub fn saved_position(&self) -> io::Result<usize> {
+        let positions = read_key_values(POSITIONS_FILE)?;
+        let position = positions
+            .get(&self.key)
+            .and_then(|position| position.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        Ok(position % self.chunks.len())
+    }
+
+    pub fn save_position(&self, position: usize) -> io::Result<()> {
+        let mut positions = read_key_values(POSITIONS_FILE)?;
+        positions.insert(self.key.clone(), (position % self.chunks.len()).to_string());
+        write_key_values(POSITIONS_FILE, &positions)
+    }
+}
+
+/// Split a text in chunks of `words_per_chunk` words separated by single spaces
+pub fn split_chunks(text: &str, words_per_chunk: Option<usize>) -> Vec<String> {
+    let words = text.split_whitespace().collect_vec();
+    let chunk_size = words_per_chunk.unwrap_or(words.len()).max(1);
+
+    words
+        .chunks(chunk_size)
+        .map(|chunk| chunk.join(" "))
+        .collect()
+}
+
+/// FNV-1a hash of the text, which stays the same across Rust versions unlike the hasher of
+/// the standard library, so saved positions of the standard input are kept
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod fnv1a_tests {
+    use super::fnv1a;
+
+    #[test]
+    fn known_hashes() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+    }
+}
+
+#[cfg(test)]
+mod split_chunks_tests {
+    use super::split_chunks;
+
+    #[test]
+    fn text_is_split_in_chunks() {
+        let result = split_chunks("a b\nc  d e", Some(2));
+
+        assert_eq!(result, vec!["a b", "c d", "e"]);
+    }
+
+    #[test]
+    fn text_without_chunk_size() {
+        let result = split_chunks("a b\nc", None);
+
+        assert_eq!(result, vec!["a b c"]);
+    }
+
+    #[test]
+    fn empty_text() {
+        let result = split_chunks(" \n ", Some(2));
+
+        assert!(result.is_empty());
+    }
+}
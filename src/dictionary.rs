@@ -0,0 +1,473 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{read_dir, read_to_string},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use rand::distributions::{Distribution, WeightedIndex};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::sentences::pick_random_words_from_dictionary;
+
+/// Writing direction of a language
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Direction {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Metadata of a dictionary, written as `# key: value` lines at the top of the file
+///
+/// Dictionaries without them are plain lists of words
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: Option<String>,
+    /// Code of the language, such as `en` or `es`
+    pub language: Option<String>,
+    pub direction: Direction,
+    /// Whether the most frequent words come first
+    pub frequency_ordered: bool,
+    /// Characters the words are expected to be written with
+    pub charset: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Words to build tests from, one per line, optionally followed by a tab and the weight of
+/// the word when picking words at random
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Dictionary {
+    pub manifest: Manifest,
+    pub words: Vec<String>,
+    /// Weight of every word, when at least one word has a weight. Words without one weigh 1
+    pub weights: Option<Vec<f64>>,
+}
+
+impl Dictionary {
+    pub fn parse(content: &str) -> Self {
+        let mut manifest = Manifest::default();
+        let mut lines = content.lines().peekable();
+        while let Some(line) = lines.next_if(|line| line.starts_with('#')) {
+            let Some((key, value)) = line.trim_start_matches('#').split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+            match key.trim() {
+                "name" => manifest.name = Some(value),
+                "language" => manifest.language = Some(value),
+                "direction" => {
+                    manifest.direction = if value == "rtl" {
+                        Direction::RightToLeft
+                    } else {
+                        Direction::LeftToRight
+                    }
+                }
+                "frequency_ordered" => manifest.frequency_ordered = value == "true",
+                "charset" => manifest.charset = Some(value),
+                "author" => manifest.author = Some(value),
+                _ => {}
+            }
+        }
+
+        let (words, weights): (Vec<_>, Vec<_>) = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| match line.split_once('\t') {
+                Some((word, weight)) => (word.to_string(), weight.trim().parse::<f64>().ok()),
+                None => (line.to_string(), None),
+            })
+            .unzip();
+        let weights = weights
+            .iter()
+            .any(Option::is_some)
+            .then(|| weights.iter().map(|weight| weight.unwrap_or(1.0)).collect());
+
+        Self {
+            manifest,
+            words,
+            weights,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&read_to_string(path)?))
+    }
+
+    pub fn words(&self) -> Vec<&str> {
+        self.words.iter().map(String::as_str).collect()
+    }
+
+    /// Pick `quantity` words at random, following their weights when there are. Without
+    /// weights, the words of a dictionary ordered by frequency weigh the inverse of the square
+    /// root of their rank, so the frequent words come up more often without repeating
+    pub fn pick_words(&self, quantity: usize) -> Vec<String> {
+        let Some(index) = self
+            .weights()
+            .and_then(|weights| WeightedIndex::new(weights).ok())
+        else {
+            return pick_random_words_from_dictionary(&self.words(), quantity);
+        };
+
+        let mut rng = rand::thread_rng();
+        (0..quantity)
+            .map(|_| self.words[index.sample(&mut rng)].clone())
+            .collect()
+    }
+
+    /// Weights to pick the words with, if any
+    fn weights(&self) -> Option<Vec<f64>> {
+        self.weights.clone().or_else(|| {
+            self.manifest.frequency_ordered.then(|| {
+                (1..=self.words.len())
+                    .map(|rank| 1.0 / (rank as f64).sqrt())
+                    .collect()
+            })
+        })
+    }
+}
+
+/// Dictionaries of the directories with words, with the path of their file. They are listed
+/// in the order of the directories, then sorted by path
+pub fn list_dictionaries(dirs: &[PathBuf]) -> Vec<(PathBuf, Dictionary)> {
+    dirs.iter()
+        .filter_map(|dir| read_dir(dir).ok())
+        .flat_map(|entries| {
+            entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+                .sorted()
+        })
+        .filter_map(|path| {
+            let dictionary = Dictionary::load(&path).ok()?;
+            (!dictionary.words.is_empty()).then_some((path, dictionary))
+        })
+        .collect()
+}
+
+/// Find the dictionary of a language among the dictionaries of the directories. The first
+/// directory with one wins, so user dictionaries can override the bundled ones
+pub fn find_language(language: &str, dirs: &[PathBuf]) -> io::Result<Dictionary> {
+    list_dictionaries(dirs)
+        .into_iter()
+        .map(|(_, dictionary)| dictionary)
+        .find(|dictionary| dictionary.manifest.language.as_deref() == Some(language))
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("there is no dictionary for the language `{}`", language),
+            )
+        })
+}
+
+/// Problem found in a line of a dictionary file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    Duplicate {
+        first_line: usize,
+    },
+    BlankLine,
+    /// Line ending with `\r\n` instead of `\n`
+    CarriageReturn,
+    /// Whitespace inside a word, which would split it in several words when typed
+    Whitespace,
+    /// Control or zero width character, which can't be seen nor typed
+    InvisibleChar(char),
+    /// Letters of several scripts in the same word, such as a Cyrillic `а` in a Latin word
+    MixedScripts(Vec<&'static str>),
+    /// Character missing from the charset of the manifest
+    OutsideCharset(char),
+    /// Word that changes with Unicode NFC normalization
+    NotNormalized,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate { first_line } => write!(f, "duplicate of line {}", first_line),
+            Self::BlankLine => write!(f, "blank line"),
+            Self::CarriageReturn => write!(f, "line ends with CRLF"),
+            Self::Whitespace => write!(f, "word contains whitespace"),
+            Self::InvisibleChar(c) => write!(f, "invisible character U+{:04X}", *c as u32),
+            Self::MixedScripts(scripts) => write!(f, "mixed scripts: {}", scripts.join(", ")),
+            Self::OutsideCharset(c) => write!(f, "`{}` isn't in the charset", c),
+            Self::NotNormalized => write!(f, "not in Unicode NFC form"),
+        }
+    }
+}
+
+/// Whether `c` can't be seen: control characters, zero width characters and byte order
+/// marks
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00AD}' | '\u{200B}'..='\u{200F}' | '\u{2028}'..='\u{202E}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+        )
+}
+
+/// Script of a letter, for the scripts words are usually mixed up with
+fn script(c: char) -> Option<&'static str> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    match c as u32 {
+        0x0000..=0x024F | 0x1E00..=0x1EFF => Some("Latin"),
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Some("Greek"),
+        0x0400..=0x052F => Some("Cyrillic"),
+        0x0590..=0x05FF => Some("Hebrew"),
+        0x0600..=0x06FF | 0x0750..=0x077F => Some("Arabic"),
+        0x0900..=0x097F => Some("Devanagari"),
+        0x3040..=0x30FF => Some("Kana"),
+        0x4E00..=0x9FFF => Some("Han"),
+        0xAC00..=0xD7AF => Some("Hangul"),
+        _ => Some("Other"),
+    }
+}
+
+/// Problems of every line of a dictionary file, with the number of the line
+pub fn check(content: &str) -> Vec<(usize, Issue)> {
+    let charset = Dictionary::parse(content).manifest.charset;
+    let mut issues = vec![];
+    let mut first_lines = HashMap::new();
+
+    let lines = content
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+    for (number, line) in lines.skip_while(|(_, line)| line.starts_with('#')) {
+        let mut line_issues = vec![];
+        if line.ends_with('\r') {
+            line_issues.push(Issue::CarriageReturn);
+        }
+        let line = line.trim_end_matches('\r');
+        let word = line.split_once('\t').map_or(line, |(word, _)| word);
+
+        if word.trim().is_empty() {
+            if number < content.split('\n').count() {
+                line_issues.push(Issue::BlankLine);
+            }
+            issues.extend(line_issues.into_iter().map(|issue| (number, issue)));
+            continue;
+        }
+
+        if word.contains(char::is_whitespace) {
+            line_issues.push(Issue::Whitespace);
+        }
+        line_issues.extend(
+            word.chars()
+                .filter(|&c| is_invisible(c))
+                .unique()
+                .map(Issue::InvisibleChar),
+        );
+        let scripts = word.chars().filter_map(script).unique().collect_vec();
+        if scripts.len() > 1 {
+            line_issues.push(Issue::MixedScripts(scripts));
+        }
+        if let Some(charset) = &charset {
+            line_issues.extend(
+                word.chars()
+                    .filter(|&c| !charset.contains(c) && !is_invisible(c) && !c.is_whitespace())
+                    .unique()
+                    .map(Issue::OutsideCharset),
+            );
+        }
+        if !is_nfc(word) {
+            line_issues.push(Issue::NotNormalized);
+        }
+
+        match first_lines.get(word) {
+            Some(&first_line) => line_issues.push(Issue::Duplicate { first_line }),
+            None => {
+                first_lines.insert(word, number);
+            }
+        }
+
+        issues.extend(line_issues.into_iter().map(|issue| (number, issue)));
+    }
+
+    issues
+}
+
+/// Clean copy of a dictionary file: words are NFC normalized and stripped of invisible
+/// characters, and blank lines, words with whitespace and duplicates are dropped. The
+/// manifest and the weights are kept
+pub fn normalize(content: &str) -> String {
+    let mut seen = HashMap::new();
+    let mut lines = content.lines().peekable();
+    let mut normalized = vec![];
+    while let Some(line) = lines.next_if(|line| line.starts_with('#')) {
+        normalized.push(line.trim_end().to_string());
+    }
+
+    for line in lines {
+        let (word, weight) = match line.split_once('\t') {
+            Some((word, weight)) => (word, Some(weight.trim())),
+            None => (line, None),
+        };
+        let word = word
+            .trim()
+            .nfc()
+            .filter(|&c| !is_invisible(c))
+            .collect::<String>();
+        if word.is_empty() || word.contains(char::is_whitespace) || seen.contains_key(&word) {
+            continue;
+        }
+
+        normalized.push(match weight {
+            Some(weight) => format!("{}\t{}", word, weight),
+            None => word.clone(),
+        });
+        seen.insert(word, ());
+    }
+
+    normalized.iter().map(|line| format!("{}\n", line)).join("")
+}
+
+#[cfg(test)]
+mod dictionary_tests {
+    use std::{env, fs, process};
+
+    use super::{find_language, list_dictionaries, Dictionary, Direction, Manifest};
+
+    #[test]
+    fn dictionary_with_manifest() {
+        let result = Dictionary::parse(
+            "# name: Hebrew\n# language: he\n# direction: rtl\n# frequency_ordered: true\n\
+             # charset: אבג\n# author: someone\nאב\nגב\n",
+        );
+
+        assert_eq!(
+            result,
+            Dictionary {
+                manifest: Manifest {
+                    name: Some("Hebrew".to_string()),
+                    language: Some("he".to_string()),
+                    direction: Direction::RightToLeft,
+                    frequency_ordered: true,
+                    charset: Some("אבג".to_string()),
+                    author: Some("someone".to_string()),
+                },
+                words: vec!["אב".to_string(), "גב".to_string()],
+                weights: None,
+            }
+        );
+    }
+
+    #[test]
+    fn plain_list_of_words() {
+        let result = Dictionary::parse("the\nof\n\nand\n");
+
+        assert_eq!(result.manifest, Manifest::default());
+        assert_eq!(result.words(), vec!["the", "of", "and"]);
+    }
+
+    #[test]
+    fn words_with_weights() {
+        let result = Dictionary::parse("the\t10\nof\nand\t0.5\n");
+
+        assert_eq!(result.words(), vec!["the", "of", "and"]);
+        assert_eq!(result.weights, Some(vec![10.0, 1.0, 0.5]));
+    }
+
+    #[test]
+    fn frequency_ordered_words_weigh_less_with_their_rank() {
+        let result = Dictionary::parse("# frequency_ordered: true\nthe\nof\nand\nto\n");
+
+        assert_eq!(
+            result.weights(),
+            Some(vec![1.0, 1.0 / 2f64.sqrt(), 1.0 / 3f64.sqrt(), 0.5])
+        );
+    }
+
+    #[test]
+    fn user_dictionaries_come_first() {
+        let root = env::temp_dir().join(format!("monclitype-dictionaries-{}", process::id()));
+        let (user_dir, bundled_dir) = (root.join("user"), root.join("bundled"));
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::create_dir_all(&bundled_dir).unwrap();
+        fs::write(bundled_dir.join("a.txt"), "# language: es\nbundled\n").unwrap();
+        fs::write(bundled_dir.join("empty.txt"), "# language: es\n").unwrap();
+        fs::write(user_dir.join("z.txt"), "# language: es\nmine\n").unwrap();
+
+        let dirs = [user_dir.clone(), bundled_dir.clone()];
+        let listed = list_dictionaries(&dirs);
+        let found = find_language("es", &dirs);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            listed
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>(),
+            vec![user_dir.join("z.txt"), bundled_dir.join("a.txt")]
+        );
+        assert_eq!(found.unwrap().words(), vec!["mine"]);
+    }
+
+    #[test]
+    fn weighted_words_are_picked() {
+        let dictionary = Dictionary::parse("the\t1\nof\t0\n");
+
+        let result = dictionary.pick_words(10);
+
+        assert_eq!(result, vec!["the"; 10]);
+    }
+}
+
+#[cfg(test)]
+mod check_tests {
+    use super::{check, normalize, Issue};
+
+    #[test]
+    fn clean_dictionary() {
+        let result = check("# charset: abc\nab\nca\n");
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn issues_are_reported_by_line() {
+        let result = check("# language: en\nab\n\nab\r\nc d\nx\u{200B}y\n");
+
+        assert_eq!(
+            result,
+            vec![
+                (3, Issue::BlankLine),
+                (4, Issue::CarriageReturn),
+                (4, Issue::Duplicate { first_line: 2 }),
+                (5, Issue::Whitespace),
+                (6, Issue::InvisibleChar('\u{200B}')),
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_scripts_and_charset() {
+        // the second letter is a Cyrillic `а`
+        let result = check("# charset: abc\nb\u{0430}\n");
+
+        assert_eq!(
+            result,
+            vec![
+                (2, Issue::MixedScripts(vec!["Latin", "Cyrillic"])),
+                (2, Issue::OutsideCharset('\u{0430}')),
+            ]
+        );
+    }
+
+    #[test]
+    fn decomposed_words_are_not_normalized() {
+        let result = check("cafe\u{0301}\n");
+
+        assert_eq!(result, vec![(1, Issue::NotNormalized)]);
+    }
+
+    #[test]
+    fn dictionary_is_normalized() {
+        let result = normalize("# language: fr\ncafe\u{0301}\t2\r\n\ncafé\nc d\nx\u{200B}y\n");
+
+        assert_eq!(result, "# language: fr\ncafé\t2\nxy\n");
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{self, ErrorKind};
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+
+use crate::ngrams::NgramTimings;
+
+/// Number of bigrams drilled when they are picked from the history
+const AUTO_NGRAMS: usize = 5;
+
+/// Times an n-gram is repeated to make a word of a repeated drill
+const REPEATS: usize = 3;
+
+/// How the text of a drill is made out of its n-grams
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DrillStyle {
+    /// Dictionary words containing the n-grams
+    #[default]
+    Words,
+    /// The n-grams themselves, repeated
+    Repeat,
+}
+
+/// The bigrams with the slowest average time in the history
+pub fn slowest_ngrams() -> io::Result<Vec<String>> {
+    let ngrams = NgramTimings::load()?
+        .slowest(2, AUTO_NGRAMS)
+        .into_iter()
+        .map(|(sequence, _)| sequence.to_string())
+        .collect_vec();
+
+    if ngrams.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            "there are no bigrams in the history yet, type a few tests first",
+        ));
+    }
+    Ok(ngrams)
+}
+
+/// Whether the n-grams given ask for the slowest bigrams of the history, which `auto` only
+/// does when it's given alone
+pub fn is_auto(ngrams: &[String]) -> io::Result<bool> {
+    match ngrams {
+        [ngram] if ngram == "auto" => Ok(true),
+        _ if ngrams.iter().any(|ngram| ngram == "auto") => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "`auto` picks the n-grams itself and can't be mixed with other n-grams",
+        )),
+        _ => Ok(false),
+    }
+}
+
+/// N-gram given on the command line, without the whitespace around it
+pub fn parse_ngram(value: &str) -> Result<String, String> {
+    let ngram = value.trim();
+    if ngram.is_empty() {
+        return Err("n-grams can't be empty".to_string());
+    }
+    if ngram.contains(char::is_whitespace) {
+        return Err(format!("`{}` contains whitespace", ngram));
+    }
+    Ok(ngram.to_string())
+}
+
+/// Words practicing every n-gram in turn
+///
+/// With [DrillStyle::Words], n-grams that no dictionary word contains are repeated instead,
+/// unless no word contains any of them
+pub fn pick_drill_words(
+    ngrams: &[String],
+    dictionary: &[&str],
+    style: DrillStyle,
+    quantity: usize,
+) -> io::Result<Vec<String>> {
+    let has_words = ngrams
+        .iter()
+        .any(|ngram| dictionary.iter().any(|word| word.contains(ngram.as_str())));
+    if style == DrillStyle::Words && !ngrams.is_empty() && !has_words {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "no word of the dictionary contains `{}`, drill them with `--drill-style repeat`",
+                ngrams.join("`, `")
+            ),
+        ));
+    }
+
+    let mut rng = rand::thread_rng();
+    Ok(ngrams
+        .iter()
+        .cycle()
+        .take(if ngrams.is_empty() { 0 } else { quantity })
+        .map(|ngram| {
+            let candidates = match style {
+                DrillStyle::Words => dictionary
+                    .iter()
+                    .filter(|word| word.contains(ngram.as_str()))
+                    .collect_vec(),
+                DrillStyle::Repeat => vec![],
+            };
+
+            candidates
+                .choose(&mut rng)
+                .map(|word| word.to_string())
+                .unwrap_or_else(|| ngram.repeat(REPEATS))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod pick_drill_words_tests {
+    use std::io::ErrorKind;
+
+    use super::{is_auto, parse_ngram, pick_drill_words, DrillStyle};
+
+    fn ngrams(ngrams: &[&str]) -> Vec<String> {
+        ngrams.iter().map(|ngram| ngram.to_string()).collect()
+    }
+
+    #[test]
+    fn repeated_ngrams() {
+        let result = pick_drill_words(&ngrams(&["th", "ing"]), &[], DrillStyle::Repeat, 3).unwrap();
+
+        assert_eq!(result, vec!["ththth", "inginging", "ththth"]);
+    }
+
+    #[test]
+    fn dictionary_words_containing_ngrams() {
+        let dictionary = ["the", "sing", "word"];
+
+        let result =
+            pick_drill_words(&ngrams(&["th", "ing"]), &dictionary, DrillStyle::Words, 4).unwrap();
+
+        assert_eq!(result, vec!["the", "sing", "the", "sing"]);
+    }
+
+    #[test]
+    fn ngrams_without_words_are_repeated() {
+        let dictionary = ["the"];
+
+        let result =
+            pick_drill_words(&ngrams(&["th", "qz"]), &dictionary, DrillStyle::Words, 2).unwrap();
+
+        assert_eq!(result, vec!["the", "qzqzqz"]);
+    }
+
+    #[test]
+    fn no_ngrams() {
+        let result = pick_drill_words(&[], &["the"], DrillStyle::Words, 2).unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn ngrams_without_any_word() {
+        let result = pick_drill_words(&ngrams(&["qz", "xj"]), &["the"], DrillStyle::Words, 2);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn ngrams_are_trimmed() {
+        assert_eq!(parse_ngram(" th "), Ok("th".to_string()));
+        assert!(parse_ngram("  ").is_err());
+        assert!(parse_ngram("t h").is_err());
+    }
+
+    #[test]
+    fn auto_is_given_alone() {
+        assert!(is_auto(&ngrams(&["auto"])).unwrap());
+        assert!(!is_auto(&ngrams(&["th", "ing"])).unwrap());
+        assert_eq!(
+            is_auto(&ngrams(&["auto", "th"])).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+}
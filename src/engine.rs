@@ -0,0 +1,657 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::{
+    splitter::{split_target_words, zip_input_target_words, InputWord, TargetWord},
+    types::{Confidence, KeyEventSource, StopOnError, TypingOptions},
+};
+
+/// Words written by the user, aligned against the words of the target sentence as key
+/// events are fed to it
+///
+/// Every character is typed, whitespace included: spaces, new lines (Enter) and tabs (Tab)
+/// have to match the separators of the target sentence. Whitespace typed in the middle of
+/// a word moves to the next one, whereas whitespace typed at the beginning of a word is
+/// ignored. Once whitespace is typed, the next word starts when the whole target separator
+/// has been typed or when the user types any other character
+///
+/// Backspace at the beginning of a word goes back to the previous word only if it has
+/// errors, and Ctrl+Backspace (or Alt+Backspace, Ctrl+W) deletes the whole word.
+/// [StopOnError] and [Confidence] options restrict which keys are accepted
+///
+/// In zen mode there is no target sentence: words are split on the whitespace typed, no
+/// key is a mistake and the game never ends
+#[derive(Debug)]
+pub struct TypingEngine<'a> {
+    target_words: Vec<TargetWord<'a>>,
+    input_words: Vec<InputWord>,
+    options: TypingOptions,
+    /// Characters typed, whitespace ignored at the beginning of a word excluded
+    keystrokes: usize,
+    /// Characters typed that didn't match the expected character
+    mistakes: usize,
+}
+
+impl<'a> TypingEngine<'a> {
+    pub fn new(target_sentence: &'a str, options: TypingOptions) -> Self {
+        Self {
+            target_words: split_target_words(target_sentence),
+            input_words: vec![InputWord::default()],
+            options,
+            keystrokes: 0,
+            mistakes: 0,
+        }
+    }
+
+    /// Build an engine and feed it every event
+    pub fn from_events(
+        events: &[KeyEventSource],
+        target_sentence: &'a str,
+        options: &TypingOptions,
+    ) -> Self {
+        let mut engine = Self::new(target_sentence, options.clone());
+        events.iter().for_each(|event| engine.handle_event(event));
+        engine
+    }
+
+    pub fn handle_event(&mut self, event: &KeyEventSource) {
+        let is_word_modifier = event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        match event.key {
+            // terminals often send Ctrl+Backspace as Ctrl+H
+            KeyCode::Char('h' | 'w') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.delete_word()
+            }
+            KeyCode::Backspace if is_word_modifier => self.delete_word(),
+            KeyCode::Char(_) if event.modifiers == KeyModifiers::CONTROL => {}
+            KeyCode::Char(c) => self.type_char(c),
+            KeyCode::Enter => self.type_char('\n'),
+            KeyCode::Tab => self.type_char('\t'),
+            KeyCode::Backspace => self.delete_char(),
+            _ => {}
+        }
+    }
+
+    pub fn target_words(&self) -> &[TargetWord<'a>] {
+        &self.target_words
+    }
+
+    /// Words written so far. There is always at least one word, the one being written
+    pub fn input_words(&self) -> &[InputWord] {
+        &self.input_words
+    }
+
+    /// Index of the word being written. It's out of the target words once the game is over
+    pub fn current_word_index(&self) -> usize {
+        self.input_words.len() - 1
+    }
+
+    /// Sentence written by the user, including the whitespace filled on their behalf
+    pub fn input_sentence(&self) -> String {
+        self.input_words
+            .iter()
+            .map(|word| format!("{}{}", word.text, word.separator))
+            .join("")
+    }
+
+    /// Characters typed, leaving out the whitespace ignored at the start of a word
+    pub fn keystrokes(&self) -> usize {
+        self.keystrokes
+    }
+
+    pub fn mistakes(&self) -> usize {
+        self.mistakes
+    }
+
+    /// Percentage of keystrokes that matched the expected character
+    pub fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+        (self.keystrokes - self.mistakes) as f64 * 100.0 / self.keystrokes as f64
+    }
+
+    /// Characters of the input matching the target sentence, separators included
+    pub fn correct_chars(&self) -> usize {
+        if self.options.zen {
+            return self.input_sentence().chars().count();
+        }
+        self.input_words
+            .iter()
+            .zip(self.target_words.iter())
+            .flat_map(|(input, target)| zip_input_target_words(input, target))
+            .filter(|pair_of_chars| matches!(pair_of_chars, EitherOrBoth::Both(a, b) if a == b))
+            .count()
+    }
+
+    /// Character the user is expected to type next
+    pub fn expected_char(&self) -> Option<char> {
+        let target_word = self.target_words.get(self.current_word_index())?;
+        let current_word = self.input_words.last().unwrap();
+
+        if current_word.separator.is_empty() {
+            target_word
+                .text
+                .chars()
+                .nth(current_word.text.chars().count())
+                .or_else(|| target_word.separator.chars().next())
+        } else {
+            target_word
+                .separator
+                .chars()
+                .nth(current_word.separator.chars().count())
+        }
+    }
+
+    fn type_char(&mut self, c: char) {
+        let expected_char = self.expected_char();
+        let target_word = self.target_words.get(self.current_word_index());
+        let current_word = self.input_words.last().unwrap();
+
+        let is_word_start = current_word.text.is_empty() && current_word.separator.is_empty();
+        if c.is_whitespace()
+            && is_word_start
+            && (self.options.zen || target_word.is_some_and(|word| !word.text.is_empty()))
+        {
+            return;
+        }
+
+        self.keystrokes += 1;
+        if !self.options.zen && expected_char != Some(c) {
+            self.mistakes += 1;
+        }
+        if !self.accepts_char(c, expected_char) {
+            return;
+        }
+
+        let target_word = self.target_words.get(self.current_word_index());
+        let current_word = self.input_words.last_mut().unwrap();
+        if !c.is_whitespace() {
+            if current_word.separator.is_empty() {
+                current_word.text.push(c);
+            } else {
+                self.input_words.push(InputWord {
+                    text: c.to_string(),
+                    separator: String::new(),
+                });
+            }
+            return;
+        }
+
+        current_word.separator.push(c);
+        if self.options.zen {
+            // the next word starts with the next character that isn't whitespace
+            return;
+        }
+        let target_separator = target_word.map_or("", |word| word.separator);
+
+        if self.options.skip_indentation && c == '\n' {
+            let indentation = target_separator
+                .chars()
+                .skip(current_word.separator.chars().count())
+                .collect::<String>();
+            if !indentation.contains('\n') {
+                current_word.separator.push_str(&indentation);
+            }
+        }
+
+        if current_word.separator.chars().count() >= target_separator.chars().count() {
+            self.input_words.push(InputWord::default());
+        }
+    }
+
+    /// Whether the stop on error and extra characters options let `c` be typed
+    fn accepts_char(&self, c: char, expected_char: Option<char>) -> bool {
+        let Some(target_word) = self.target_words.get(self.current_word_index()) else {
+            return true;
+        };
+        let current_word = self.input_words.last().unwrap();
+
+        let is_extra_char = !c.is_whitespace() && current_word.separator.is_empty();
+        if is_extra_char
+            && self.options.max_extra_chars.is_some_and(|max_extra_chars| {
+                current_word.text.chars().count()
+                    >= target_word.text.chars().count() + max_extra_chars
+            })
+        {
+            return false;
+        }
+
+        match self.options.stop_on_error {
+            StopOnError::Off => true,
+            StopOnError::Letter => expected_char == Some(c),
+            StopOnError::Word if c.is_whitespace() => {
+                current_word.text == target_word.text
+                    && target_word
+                        .separator
+                        .starts_with(&format!("{}{}", current_word.separator, c))
+            }
+            // any other character would skip the rest of the separator
+            StopOnError::Word => current_word.separator.is_empty(),
+        }
+    }
+
+    /// Whether backspace at the beginning of the current word can go back to the previous
+    /// one. It's only allowed when the previous word has errors, or always in zen mode
+    fn can_go_back(&self) -> bool {
+        let index = self.current_word_index();
+        if index == 0 || self.options.confidence != Confidence::Off {
+            return false;
+        }
+        if self.options.zen {
+            return true;
+        }
+
+        let previous_word = &self.input_words[index - 1];
+        self.target_words.get(index - 1).is_some_and(|target_word| {
+            previous_word.text != target_word.text
+                || previous_word.separator != target_word.separator
+        })
+    }
+
+    fn delete_char(&mut self) {
+        if self.options.confidence == Confidence::Max {
+            return;
+        }
+
+        let current_word = self.input_words.last_mut().unwrap();
+        if current_word.separator.pop().is_some() || current_word.text.pop().is_some() {
+            return;
+        }
+        if !self.can_go_back() {
+            return;
+        }
+
+        self.input_words.pop();
+        let previous_word = self.input_words.last_mut().unwrap();
+        if self.options.skip_indentation {
+            // the indentation was filled along with the new line, so it goes away with it
+            let line_start = previous_word.separator.trim_end_matches([' ', '\t']).len();
+            if previous_word.separator[..line_start].ends_with('\n') {
+                previous_word.separator.truncate(line_start);
+            }
+        }
+        previous_word.separator.pop();
+    }
+
+    /// Delete the current word, or the previous one when the current word is empty
+    fn delete_word(&mut self) {
+        if self.options.confidence == Confidence::Max {
+            return;
+        }
+
+        let current_word = self.input_words.last().unwrap();
+        let is_empty = current_word.text.is_empty() && current_word.separator.is_empty();
+        if is_empty {
+            if !self.can_go_back() {
+                return;
+            }
+            self.input_words.pop();
+        }
+
+        *self.input_words.last_mut().unwrap() = InputWord::default();
+    }
+}
+
+#[cfg(test)]
+mod typing_engine_tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::{
+        splitter::InputWord,
+        types::{Confidence, StopOnError, TypingOptions},
+        views::test_harness::ScriptedEvents,
+    };
+
+    use super::TypingEngine;
+
+    fn input_word(text: &str, separator: &str) -> InputWord {
+        InputWord {
+            text: text.to_string(),
+            separator: separator.to_string(),
+        }
+    }
+
+    #[test]
+    fn empty_sentence() {
+        let engine = TypingEngine::from_events(&[], "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_sentence().as_str(), "");
+    }
+
+    #[test]
+    fn non_empty_sentence() {
+        let events = ScriptedEvents::new().type_text("h w").events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_sentence().as_str(), "h w");
+    }
+
+    #[test]
+    fn non_empty_sentence_with_backspace() {
+        let events = ScriptedEvents::new()
+            .type_text("hw")
+            .press(KeyCode::Backspace)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_sentence().as_str(), "h");
+    }
+
+    #[test]
+    fn empty_sentence_with_overuse_of_backspaces() {
+        let events = ScriptedEvents::new()
+            .type_text("x ")
+            .press(KeyCode::Backspace)
+            .press(KeyCode::Backspace)
+            .press(KeyCode::Backspace)
+            .press(KeyCode::Backspace)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_words(), [input_word("", "")]);
+    }
+
+    #[test]
+    fn overuse_of_spaces() {
+        let events = ScriptedEvents::new().type_text("   ").events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_sentence().as_str(), "");
+    }
+
+    #[test]
+    fn multiple_spaces_are_typed() {
+        let events = ScriptedEvents::new().type_text("a  b").events();
+
+        let engine = TypingEngine::from_events(&events, "a  b", &TypingOptions::default());
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("a", "  "), input_word("b", "")]
+        );
+    }
+
+    #[test]
+    fn enter_and_tab_are_typed() {
+        let events = ScriptedEvents::new().type_text("{\n\ta").events();
+
+        let engine = TypingEngine::from_events(&events, "{\n\ta", &TypingOptions::default());
+
+        assert_eq!(engine.input_sentence().as_str(), "{\n\ta");
+        assert_eq!(engine.current_word_index(), 1);
+    }
+
+    #[test]
+    fn indentation_has_to_be_typed() {
+        let events = ScriptedEvents::new().type_text("{\n ").events();
+
+        let engine = TypingEngine::from_events(&events, "{\n  a", &TypingOptions::default());
+
+        assert_eq!(engine.input_words(), [input_word("{", "\n ")]);
+    }
+
+    #[test]
+    fn typing_a_character_skips_the_rest_of_the_separator() {
+        let events = ScriptedEvents::new().type_text("{\na").events();
+
+        let engine = TypingEngine::from_events(&events, "{\n  a", &TypingOptions::default());
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("{", "\n"), input_word("a", "")]
+        );
+    }
+
+    #[test]
+    fn indentation_is_skipped() {
+        let events = ScriptedEvents::new().type_text("{\na").events();
+        let options = TypingOptions {
+            skip_indentation: true,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "{\n\t\ta", &options);
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("{", "\n\t\t"), input_word("a", "")]
+        );
+    }
+
+    #[test]
+    fn skipped_indentation_is_deleted_with_its_new_line() {
+        let events = ScriptedEvents::new()
+            .type_text("(\n")
+            .press(KeyCode::Backspace)
+            .events();
+        let options = TypingOptions {
+            skip_indentation: true,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "{\n  a", &options);
+
+        assert_eq!(engine.input_words(), [input_word("(", "")]);
+    }
+
+    #[test]
+    fn mistakes_are_counted_even_if_corrected() {
+        let events = ScriptedEvents::new()
+            .type_text(" hx")
+            .press(KeyCode::Backspace)
+            .type_text(" w")
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.accuracy(), 75.0);
+        assert_eq!(engine.correct_chars(), 3);
+    }
+
+    #[test]
+    fn stop_on_letter_rejects_wrong_keys() {
+        let events = ScriptedEvents::new().type_text("hx i").events();
+        let options = TypingOptions {
+            stop_on_error: StopOnError::Letter,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "hi w", &options);
+
+        assert_eq!(engine.input_words(), [input_word("hi", "")]);
+        assert_eq!(engine.accuracy(), 50.0);
+    }
+
+    #[test]
+    fn stop_on_word_rejects_moving_past_a_wrong_word() {
+        let events = ScriptedEvents::new()
+            .type_text("hx ")
+            .press(KeyCode::Backspace)
+            .type_text("i ")
+            .events();
+        let options = TypingOptions {
+            stop_on_error: StopOnError::Word,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "hi w", &options);
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("hi", " "), input_word("", "")]
+        );
+    }
+
+    #[test]
+    fn stop_on_word_rejects_skipping_the_separator() {
+        let events = ScriptedEvents::new().type_text("{\na").events();
+        let options = TypingOptions {
+            stop_on_error: StopOnError::Word,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "{\n  a", &options);
+
+        assert_eq!(engine.input_words(), [input_word("{", "\n")]);
+    }
+
+    #[test]
+    fn confidence_rejects_going_back_to_previous_words() {
+        let events = ScriptedEvents::new()
+            .type_text("h x")
+            .press(KeyCode::Backspace)
+            .press(KeyCode::Backspace)
+            .events();
+        let options = TypingOptions {
+            confidence: Confidence::On,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "h w", &options);
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("h", " "), input_word("", "")]
+        );
+    }
+
+    #[test]
+    fn max_confidence_disables_backspace() {
+        let events = ScriptedEvents::new()
+            .type_text("x")
+            .press(KeyCode::Backspace)
+            .events();
+        let options = TypingOptions {
+            confidence: Confidence::Max,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "h w", &options);
+
+        assert_eq!(engine.input_words(), [input_word("x", "")]);
+    }
+
+    #[test]
+    fn backspace_does_not_go_back_to_a_correct_word() {
+        let events = ScriptedEvents::new()
+            .type_text("h ")
+            .press(KeyCode::Backspace)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("h", " "), input_word("", "")]
+        );
+    }
+
+    #[test]
+    fn backspace_goes_back_to_a_skipped_word() {
+        let events = ScriptedEvents::new()
+            .type_text("h ")
+            .press(KeyCode::Backspace)
+            .type_text("i")
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "hi w", &TypingOptions::default());
+
+        assert_eq!(engine.input_words(), [input_word("hi", "")]);
+    }
+
+    #[test]
+    fn ctrl_backspace_deletes_the_current_word() {
+        let events = ScriptedEvents::new()
+            .type_text("h wx")
+            .press_with(KeyCode::Backspace, KeyModifiers::CONTROL)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h wo", &TypingOptions::default());
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("h", " "), input_word("", "")]
+        );
+    }
+
+    #[test]
+    fn alt_backspace_deletes_the_previous_word_with_errors() {
+        let events = ScriptedEvents::new()
+            .type_text("x ")
+            .press_with(KeyCode::Backspace, KeyModifiers::ALT)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_words(), [input_word("", "")]);
+    }
+
+    #[test]
+    fn control_characters_are_not_typed() {
+        let events = ScriptedEvents::new()
+            .type_text("h")
+            .press_with(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            .events();
+
+        let engine = TypingEngine::from_events(&events, "h w", &TypingOptions::default());
+
+        assert_eq!(engine.input_words(), [input_word("h", "")]);
+    }
+
+    #[test]
+    fn extra_chars_are_capped() {
+        let events = ScriptedEvents::new().type_text("hxyz").events();
+        let options = TypingOptions {
+            max_extra_chars: Some(2),
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "h w", &options);
+
+        assert_eq!(engine.input_words(), [input_word("hxy", "")]);
+        assert_eq!(engine.mistakes(), 3);
+    }
+
+    #[test]
+    fn zen_words_are_split_on_whitespace() {
+        let events = ScriptedEvents::new().type_text(" a \nb").events();
+        let options = TypingOptions {
+            zen: true,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "", &options);
+
+        assert_eq!(
+            engine.input_words(),
+            [input_word("a", " \n"), input_word("b", "")]
+        );
+        assert_eq!(engine.mistakes(), 0);
+        assert_eq!(engine.correct_chars(), 4);
+    }
+
+    #[test]
+    fn zen_backspace_goes_back_to_previous_words() {
+        let events = ScriptedEvents::new()
+            .type_text("a b")
+            .press(KeyCode::Backspace)
+            .press(KeyCode::Backspace)
+            .type_text("c")
+            .events();
+        let options = TypingOptions {
+            zen: true,
+            ..Default::default()
+        };
+
+        let engine = TypingEngine::from_events(&events, "", &options);
+
+        assert_eq!(engine.input_sentence(), "ac");
+    }
+}
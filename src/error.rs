@@ -0,0 +1,130 @@
+use std::{
+    fmt::Display,
+    io::{self, ErrorKind},
+};
+
+/// Error ending a run, reported once the terminal is restored
+#[derive(Debug)]
+pub enum AppError {
+    /// The dictionary couldn't be found or read
+    Dictionary { source: String, error: io::Error },
+    /// The dictionary has no words to pick from
+    EmptyDictionary(String),
+    /// `dictionary check` found issues in the dictionary
+    DictionaryIssues { source: String, count: usize },
+    /// An option points to something missing or malformed, such as a file to type
+    InvalidConfig {
+        option: &'static str,
+        error: io::Error,
+    },
+    /// The terminal couldn't be set up, drawn on or read from
+    Terminal(io::Error),
+    /// The data kept between runs couldn't be read or saved
+    Io(io::Error),
+}
+
+impl AppError {
+    /// Error of the option `option`, such as a file that can't be read
+    pub fn config(option: &'static str) -> impl FnOnce(io::Error) -> Self {
+        move |error| Self::InvalidConfig { option, error }
+    }
+
+    /// Error reading the dictionary `source`
+    pub fn dictionary(source: &str) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |error| Self::Dictionary {
+            source: source.to_string(),
+            error,
+        }
+    }
+
+    /// Code the program exits with, 2 being left to the command line parser
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Io(_) => 1,
+            Self::InvalidConfig { .. } => 3,
+            Self::Dictionary { .. } => 4,
+            Self::EmptyDictionary(_) => 5,
+            Self::Terminal(_) => 6,
+            Self::DictionaryIssues { .. } => 7,
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dictionary { source, error } if error.kind() == ErrorKind::NotFound => {
+                write!(f, "the dictionary `{}` can't be found", source)
+            }
+            Self::Dictionary { source, error } => {
+                write!(f, "the dictionary `{}` can't be read: {}", source, error)
+            }
+            Self::EmptyDictionary(source) => {
+                write!(f, "the dictionary `{}` doesn't contain any words", source)
+            }
+            Self::DictionaryIssues { source, count } => {
+                write!(f, "the dictionary `{}` has {} issue(s)", source, count)
+            }
+            Self::InvalidConfig { option, error } => write!(f, "invalid `{}`: {}", option, error),
+            Self::Terminal(error) => write!(f, "the terminal stopped working: {}", error),
+            Self::Io(error) => write!(f, "the saved data can't be read or written: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use std::io::{self, ErrorKind};
+
+    use itertools::Itertools;
+
+    use super::AppError;
+
+    #[test]
+    fn every_error_exits_with_its_own_code() {
+        let errors = [
+            AppError::Io(io::Error::from(ErrorKind::PermissionDenied)),
+            AppError::config("--text")(io::Error::from(ErrorKind::NotFound)),
+            AppError::dictionary("fr")(io::Error::from(ErrorKind::NotFound)),
+            AppError::EmptyDictionary("src/dict/empty.txt".to_string()),
+            AppError::Terminal(io::Error::from(ErrorKind::BrokenPipe)),
+            AppError::DictionaryIssues {
+                source: "words.txt".to_string(),
+                count: 2,
+            },
+        ];
+
+        let codes = errors.iter().map(AppError::exit_code).collect_vec();
+
+        assert!(codes.iter().all_unique());
+        assert!(codes.iter().all(|&code| code != 0 && code != 2));
+    }
+
+    #[test]
+    fn missing_dictionaries_are_named() {
+        let error = AppError::dictionary("fr")(io::Error::from(ErrorKind::NotFound));
+
+        assert_eq!(error.to_string(), "the dictionary `fr` can't be found");
+    }
+
+    #[test]
+    fn invalid_options_are_named() {
+        let error = AppError::config("--replay")(io::Error::new(
+            ErrorKind::InvalidData,
+            "a replay has to start with its target text",
+        ));
+
+        assert_eq!(
+            error.to_string(),
+            "invalid `--replay`: a replay has to start with its target text"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::{engine::TypingEngine, metrics::words_per_minute};
+
+/// Conditions that end a test as failed before the text is completed
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FailConditions {
+    /// Fail on the first mistake
+    pub sudden_death: bool,
+    /// Fail when the running accuracy, in percentage, drops below this value
+    pub min_accuracy: Option<f64>,
+    /// Fail when the running speed, in words per minute, drops below this value
+    pub min_wpm: Option<f64>,
+    /// Time since the first key during which accuracy and speed aren't checked
+    pub grace_period: Duration,
+}
+
+/// Why a test failed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailReason {
+    Mistake,
+    LowAccuracy(f64),
+    LowWpm(f64),
+}
+
+impl Display for FailReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mistake => write!(f, "a mistake was made"),
+            Self::LowAccuracy(threshold) => write!(f, "accuracy dropped below {}%", threshold),
+            Self::LowWpm(threshold) => write!(f, "speed dropped below {} wpm", threshold),
+        }
+    }
+}
+
+impl FailConditions {
+    /// Check the conditions against the input so far, `elapsed` after the first key
+    pub fn check(&self, engine: &TypingEngine, elapsed: Duration) -> Option<FailReason> {
+        if self.sudden_death && engine.mistakes() > 0 {
+            return Some(FailReason::Mistake);
+        }
+        if elapsed < self.grace_period {
+            return None;
+        }
+
+        if let Some(min_accuracy) = self.min_accuracy.filter(|&min| engine.accuracy() < min) {
+            return Some(FailReason::LowAccuracy(min_accuracy));
+        }
+        let wpm = words_per_minute(engine.correct_chars(), elapsed);
+        if let Some(min_wpm) = self.min_wpm.filter(|&min| wpm < min) {
+            return Some(FailReason::LowWpm(min_wpm));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod fail_conditions_tests {
+    use std::time::Duration;
+
+    use crate::{engine::TypingEngine, types::TypingOptions, views::test_harness::ScriptedEvents};
+
+    use super::{FailConditions, FailReason};
+
+    fn engine<'a>(typed: &str, target: &'a str) -> TypingEngine<'a> {
+        let events = ScriptedEvents::new().type_text(typed).events();
+        TypingEngine::from_events(&events, target, &TypingOptions::default())
+    }
+
+    #[test]
+    fn sudden_death_fails_on_first_mistake() {
+        let conditions = FailConditions {
+            sudden_death: true,
+            grace_period: Duration::from_secs(5),
+            ..Default::default()
+        };
+
+        let result = conditions.check(&engine("hx", "hello"), Duration::ZERO);
+
+        assert_eq!(result, Some(FailReason::Mistake));
+    }
+
+    #[test]
+    fn thresholds_are_not_checked_during_grace_period() {
+        let conditions = FailConditions {
+            min_accuracy: Some(90.0),
+            min_wpm: Some(200.0),
+            grace_period: Duration::from_secs(5),
+            ..Default::default()
+        };
+
+        let result = conditions.check(&engine("hx", "hello"), Duration::from_secs(1));
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn low_accuracy() {
+        let conditions = FailConditions {
+            min_accuracy: Some(90.0),
+            ..Default::default()
+        };
+
+        let result = conditions.check(&engine("hx", "hello"), Duration::from_secs(1));
+
+        assert_eq!(result, Some(FailReason::LowAccuracy(90.0)));
+    }
+
+    #[test]
+    fn low_wpm() {
+        let conditions = FailConditions {
+            min_wpm: Some(30.0),
+            ..Default::default()
+        };
+
+        let result = conditions.check(&engine("hello", "hello"), Duration::from_secs(6));
+
+        assert_eq!(result, Some(FailReason::LowWpm(30.0)));
+    }
+}
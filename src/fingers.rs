@@ -0,0 +1,251 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::Itertools;
+
+use crate::{
+    engine::TypingEngine,
+    layout::{Finger, Layout},
+    types::{KeyEventSource, TypingOptions},
+};
+
+/// Keys typed by a finger during a test
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FingerStats {
+    pub keystrokes: usize,
+    /// Keys that didn't match the expected character
+    pub mistakes: usize,
+    /// Time since the previous key, added up over the keys typed after another key
+    pub total_time: Duration,
+    pub timed_keystrokes: usize,
+}
+
+impl FingerStats {
+    pub fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+        (self.keystrokes - self.mistakes) as f64 * 100.0 / self.keystrokes as f64
+    }
+
+    /// Average time to reach a key of the finger
+    pub fn average_time(&self) -> Duration {
+        self.total_time / self.timed_keystrokes.max(1) as u32
+    }
+}
+
+/// How the keys of a test were spread over the fingers, following the finger assigned to
+/// every key by the layout
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FingerAnalytics {
+    pub fingers: BTreeMap<Finger, FingerStats>,
+    /// Pairs of consecutive keys typed
+    pub bigrams: usize,
+    /// Pairs of consecutive different keys typed with the same finger
+    pub same_finger_bigrams: usize,
+}
+
+impl FingerAnalytics {
+    pub fn new(
+        events: &[KeyEventSource],
+        target_sentence: &str,
+        options: &TypingOptions,
+        layout: &Layout,
+    ) -> Self {
+        let mut analytics = Self::default();
+        let mut engine = TypingEngine::new(target_sentence, options.clone());
+        let mut previous_key: Option<(char, Finger)> = None;
+        let mut previous_timestamp = None;
+
+        for event in events {
+            // mistakes are the ones of the engine, so the accuracy of the fingers adds up to
+            // the accuracy of the test
+            let (keystrokes, mistakes) = (engine.keystrokes(), engine.mistakes());
+            engine.handle_event(event);
+            let counted = engine.keystrokes() > keystrokes;
+            let is_mistake = engine.mistakes() > mistakes;
+
+            let typed_char = match event.key {
+                KeyCode::Char(c) if event.modifiers != KeyModifiers::CONTROL => Some(c),
+                KeyCode::Enter => Some('\n'),
+                KeyCode::Tab => Some('\t'),
+                _ => None,
+            };
+            let Some((c, finger)) = typed_char
+                .filter(|_| counted)
+                .and_then(|c| Some((c, Finger::for_char(layout, c)?)))
+            else {
+                previous_key = None;
+                previous_timestamp = Some(event.timestamp);
+                continue;
+            };
+
+            let stats = analytics.fingers.entry(finger).or_default();
+            stats.keystrokes += 1;
+            if is_mistake {
+                stats.mistakes += 1;
+            }
+            if let Some(previous_timestamp) = previous_timestamp {
+                stats.total_time += event.timestamp.duration_since(previous_timestamp);
+                stats.timed_keystrokes += 1;
+            }
+
+            if let Some((previous_char, previous_finger)) = previous_key {
+                analytics.bigrams += 1;
+                if previous_finger == finger && previous_char != c {
+                    analytics.same_finger_bigrams += 1;
+                }
+            }
+            previous_key = Some((c, finger));
+            previous_timestamp = Some(event.timestamp);
+        }
+
+        analytics
+    }
+
+    /// Percentage of the keys typed by `finger`
+    pub fn load(&self, finger: Finger) -> f64 {
+        let keystrokes = self
+            .fingers
+            .values()
+            .map(|stats| stats.keystrokes)
+            .sum::<usize>();
+        let finger_keystrokes = self
+            .fingers
+            .get(&finger)
+            .map_or(0, |stats| stats.keystrokes);
+        if keystrokes == 0 {
+            return 0.0;
+        }
+        finger_keystrokes as f64 * 100.0 / keystrokes as f64
+    }
+
+    /// Percentage of the pairs of consecutive keys typed with the same finger
+    pub fn same_finger_bigram_rate(&self) -> f64 {
+        if self.bigrams == 0 {
+            return 0.0;
+        }
+        self.same_finger_bigrams as f64 * 100.0 / self.bigrams as f64
+    }
+
+    /// Load, accuracy and average time in milliseconds of every finger used, as stored in
+    /// the history: `finger:load:accuracy:milliseconds` separated by spaces
+    pub fn to_field(&self) -> String {
+        self.fingers
+            .iter()
+            .map(|(&finger, stats)| {
+                format!(
+                    "{}:{:.1}:{:.1}:{}",
+                    finger.short_name(),
+                    self.load(finger),
+                    stats.accuracy(),
+                    stats.average_time().as_millis()
+                )
+            })
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod finger_analytics_tests {
+    use std::time::Duration;
+
+    use crate::{
+        layout::{Finger, Layout},
+        types::TypingOptions,
+        views::test_harness::ScriptedEvents,
+    };
+
+    use super::FingerAnalytics;
+
+    #[test]
+    fn keys_are_attributed_to_fingers() {
+        let result = FingerAnalytics::new(
+            &ScriptedEvents::new()
+                .type_at("dex", &[0, 100, 200])
+                .events(),
+            "dec",
+            &TypingOptions::default(),
+            &Layout::qwerty(),
+        );
+
+        let left_middle = result.fingers[&Finger::LeftMiddle];
+        let left_ring = result.fingers[&Finger::LeftRing];
+        assert_eq!(left_middle.keystrokes, 2);
+        assert_eq!(left_middle.average_time(), Duration::from_millis(100));
+        assert_eq!(left_ring.accuracy(), 0.0);
+        assert_eq!(result.load(Finger::LeftMiddle), 200.0 / 3.0);
+    }
+
+    #[test]
+    fn same_finger_bigrams() {
+        let result = FingerAnalytics::new(
+            &ScriptedEvents::new().type_text("deed").events(),
+            "deed",
+            &TypingOptions::default(),
+            &Layout::qwerty(),
+        );
+
+        // "de" and "ed" use the left middle finger, "ee" is the same key
+        assert_eq!(result.bigrams, 3);
+        assert_eq!(result.same_finger_bigrams, 2);
+    }
+
+    #[test]
+    fn fingers_are_stored_in_a_field() {
+        let result = FingerAnalytics::new(
+            &ScriptedEvents::new().type_at("fj", &[0, 100]).events(),
+            "fj",
+            &TypingOptions::default(),
+            &Layout::qwerty(),
+        );
+
+        assert_eq!(result.to_field(), "li:50.0:100.0:0 ri:50.0:100.0:100");
+    }
+
+    #[test]
+    fn fingers_follow_the_layout_assignment() {
+        // `h` and `j` are pressed with the left index instead of the right one
+        let definition = format!(
+            "{}\nfingers 0012334556788\nfingers 0123355678888\nfingers 01233336788\nfingers 0123355678",
+            include_str!("../layouts/qwerty.txt")
+        );
+        let layout = Layout::parse("custom", &definition).unwrap();
+
+        let result = FingerAnalytics::new(
+            &ScriptedEvents::new().type_at("fj", &[0, 100]).events(),
+            "fj",
+            &TypingOptions::default(),
+            &layout,
+        );
+
+        assert_eq!(result.fingers[&Finger::LeftIndex].keystrokes, 2);
+        assert!(!result.fingers.contains_key(&Finger::RightIndex));
+        assert_eq!(result.same_finger_bigrams, 1);
+    }
+
+    #[test]
+    fn ignored_indentation_is_not_a_mistake() {
+        let options = TypingOptions {
+            skip_indentation: true,
+            ..Default::default()
+        };
+
+        let result = FingerAnalytics::new(
+            &ScriptedEvents::new().type_text("a\n  b").events(),
+            "a\n  b",
+            &options,
+            &Layout::qwerty(),
+        );
+
+        assert!(result.fingers.values().all(|stats| stats.mistakes == 0));
+        assert_eq!(
+            result
+                .fingers
+                .values()
+                .map(|stats| stats.keystrokes)
+                .sum::<usize>(),
+            3
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+
+/// Words used to fill the generated text when the dictionary has none that fit
+const FALLBACK_WORDS: [&str; 8] = [
+    "foo", "bar", "data", "value", "item", "user", "config", "index",
+];
+
+/// Longest dictionary word used in generated identifiers and commands
+const MAX_WORD_LENGTH: usize = 8;
+
+/// Tokens of punctuation common in source code
+const SYMBOLS: [&str; 32] = [
+    "{}", "()", "[]", "<>", "->", "=>", "::", "!=", "==", "<=", ">=", "&&", "||", "+=", "-=", "*=",
+    "..", "..=", "//", "/*", "*/", "#[", "${", "?;", "();", "&mut", "%s", "@", "$", "^", "~",
+    "|x|",
+];
+
+/// Symbol heavy tokens around a word, `_` standing for the word
+const SYMBOL_TEMPLATES: [&str; 24] = [
+    "{_}", "(_)", "[_]", "<_>", "&_", "*_", "!_", "_?", "_!", "_();", "_::_", "_->_", "_=>_",
+    "_!=_", "_==_", "_+=1", "\"_\"", "'_'", "$_", "#_", "@_", "|_|", "_[0]", "_.._",
+];
+
+/// Shell commands, `_` standing for a word
+const SHELL_TEMPLATES: [&str; 24] = [
+    "ls -la _/",
+    "cd ~/_/_",
+    "mkdir -p _/_",
+    "git status",
+    "git checkout -b _",
+    "git commit -m \"_ _\"",
+    "git push origin _",
+    "git log --oneline -n 5",
+    "grep -rn _ src/",
+    "find . -name \"*._\"",
+    "cat _.txt | wc -l",
+    "ps aux | grep _",
+    "kill -9 $(pgrep _)",
+    "cp _.rs _/",
+    "rm -rf ./_",
+    "chmod +x _.sh",
+    "export _=_",
+    "echo $_",
+    "tar -xzf _.tar.gz",
+    "curl -s https://_.com/_",
+    "ssh _@_",
+    "docker run -it _",
+    "cargo test _",
+    "sudo apt install _",
+];
+
+/// Kind of text generated for a test, instead of picking words from the dictionary
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Generator {
+    /// Digit sequences and numbers
+    Numbers,
+    /// Punctuation of source code, alone or around words
+    Symbols,
+    /// Identifiers made of words, in snake_case, camelCase and other cases
+    Identifiers,
+    /// Shell commands, one per line
+    Shell,
+}
+
+impl Generator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Numbers => "numbers",
+            Self::Symbols => "symbols",
+            Self::Identifiers => "identifiers",
+            Self::Shell => "shell",
+        }
+    }
+}
+
+/// Text of about `quantity` words made by `generator`, with words of the dictionary where
+/// the text needs some
+pub fn generate_text(generator: Generator, dictionary: &[&str], quantity: usize) -> String {
+    let words = usable_words(dictionary);
+    match generator {
+        Generator::Numbers => (0..quantity).map(|_| pick_number()).join(" "),
+        Generator::Symbols => (0..quantity).map(|_| pick_symbol(&words)).join(" "),
+        Generator::Identifiers => (0..quantity).map(|_| pick_identifier(&words)).join(" "),
+        Generator::Shell => pick_shell_commands(&words, quantity).join("\n"),
+    }
+}
+
+/// Short lowercase ASCII words of the dictionary, which fit in code and commands
+fn usable_words<'a>(dictionary: &[&'a str]) -> Vec<&'a str> {
+    let words = dictionary
+        .iter()
+        .filter(|word| (2..=MAX_WORD_LENGTH).contains(&word.len()))
+        .filter(|word| word.chars().all(|c| c.is_ascii_lowercase()))
+        .copied()
+        .collect_vec();
+    if words.is_empty() {
+        FALLBACK_WORDS.to_vec()
+    } else {
+        words
+    }
+}
+
+fn pick_word<'a>(words: &[&'a str]) -> &'a str {
+    words.choose(&mut rand::thread_rng()).unwrap()
+}
+
+/// Replace every `_` of the template with a random word
+fn fill_template(template: &str, words: &[&str]) -> String {
+    Itertools::intersperse_with(template.split('_'), || pick_word(words)).collect()
+}
+
+/// A digit sequence, sometimes negative, decimal or grouped by thousands
+fn pick_number() -> String {
+    let mut rng = rand::thread_rng();
+    let digits = (0..rng.gen_range(1..=6))
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect::<String>();
+
+    match rng.gen_range(0..10) {
+        0 => format!("-{}", digits),
+        1 => format!("{}.{}", digits, rng.gen_range(0..100)),
+        2 if digits.len() > 3 => {
+            let (thousands, units) = digits.split_at(digits.len() - 3);
+            format!("{},{}", thousands, units)
+        }
+        _ => digits,
+    }
+}
+
+/// A symbol alone or around words
+fn pick_symbol(words: &[&str]) -> String {
+    let mut rng = rand::thread_rng();
+    if rng.gen_bool(0.4) {
+        SYMBOLS.choose(&mut rng).unwrap().to_string()
+    } else {
+        fill_template(SYMBOL_TEMPLATES.choose(&mut rng).unwrap(), words)
+    }
+}
+
+/// Two or three words joined in one of the cases identifiers are written in
+fn pick_identifier(words: &[&str]) -> String {
+    let mut rng = rand::thread_rng();
+    let parts = (0..rng.gen_range(2..=3))
+        .map(|_| pick_word(words))
+        .collect_vec();
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+    };
+
+    match rng.gen_range(0..5) {
+        0 => parts.join("_"),
+        1 => parts[0].to_string() + &parts[1..].iter().map(|part| capitalize(part)).join(""),
+        2 => parts.iter().map(|part| capitalize(part)).join(""),
+        3 => parts.join("_").to_ascii_uppercase(),
+        _ => parts.join("-"),
+    }
+}
+
+/// Commands adding up to at least `quantity` words
+fn pick_shell_commands(words: &[&str], quantity: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    let mut commands = vec![];
+    let mut total_words = 0;
+    while total_words < quantity {
+        let command = fill_template(SHELL_TEMPLATES.choose(&mut rng).unwrap(), words);
+        total_words += command.split_whitespace().count();
+        commands.push(command);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod generators_tests {
+    use super::{fill_template, generate_text, usable_words, Generator, FALLBACK_WORDS};
+
+    #[test]
+    fn numbers_are_made_of_digits() {
+        let result = generate_text(Generator::Numbers, &[], 50);
+
+        let numbers = result.split(' ').collect::<Vec<_>>();
+        assert_eq!(numbers.len(), 50);
+        assert!(numbers
+            .iter()
+            .all(|number| number.chars().any(|c| c.is_ascii_digit())
+                && number
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || "-.,".contains(c))));
+    }
+
+    #[test]
+    fn identifiers_are_made_of_dictionary_words() {
+        let result = generate_text(Generator::Identifiers, &["red", "fox"], 20);
+
+        assert_eq!(result.split(' ').count(), 20);
+        assert!(result
+            .split(' ')
+            .all(|identifier| identifier.to_ascii_lowercase().contains("red")
+                || identifier.to_ascii_lowercase().contains("fox")));
+    }
+
+    #[test]
+    fn shell_commands_are_on_their_own_lines() {
+        let result = generate_text(Generator::Shell, &["app"], 10);
+
+        assert!(result.split_whitespace().count() >= 10);
+        assert!(result.lines().count() > 1);
+        assert!(!result.contains('_'));
+        assert!(!result.ends_with('\n'));
+    }
+
+    #[test]
+    fn templates_are_filled_with_words() {
+        let result = fill_template("_::_", &["io"]);
+
+        assert_eq!(result, "io::io");
+    }
+
+    #[test]
+    fn unusable_dictionaries_fall_back_to_generic_words() {
+        let result = usable_words(&["Über", "a", "straßenbahn"]);
+
+        assert_eq!(result, FALLBACK_WORDS.to_vec());
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use itertools::Itertools;
+
+use crate::{
+    fingers::FingerAnalytics,
+    metrics::RunSummary,
+    storage::{append_line, read_lines},
+};
+
+const HISTORY_FILE: &str = "history.tsv";
+
+/// A test stored in the history
+///
+/// Entries are stored one per line as tab separated `key=value` fields, so fields can be
+/// added without breaking older histories. Unknown fields are ignored when reading
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch when the test was finished
+    pub timestamp: u64,
+    /// Where the text came from: `words`, `code`, `text`, `book`, `drill`, `lesson`,
+    /// `generated` or `zen`. Replays aren't stored, their test was when it was recorded
+    pub mode: String,
+    /// Dictionary, file or language the text came from
+    pub source: Option<String>,
+    /// Chapter of the book the text belongs to
+    pub chapter: Option<String>,
+    pub wpm: f64,
+    pub accuracy: f64,
+    /// Duration of the test in seconds
+    pub duration: f64,
+    pub completed: bool,
+    /// Why the test failed, when a fail condition was met
+    pub fail_reason: Option<String>,
+    /// Missing from entries recorded before consistency was measured
+    pub consistency: Option<f64>,
+    pub burst_wpm: Option<f64>,
+    pub slowest_words: Vec<String>,
+    /// Percentage of consecutive keys typed with the same finger
+    pub same_finger_bigrams: Option<f64>,
+    /// Load, accuracy and speed of every finger, see [FingerAnalytics::to_field]
+    pub fingers: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(mode: &str, summary: &RunSummary) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            mode: mode.to_string(),
+            wpm: summary.wpm,
+            accuracy: summary.accuracy,
+            duration: summary.duration.as_secs_f64(),
+            completed: summary.completed,
+            fail_reason: summary.fail_reason.map(|reason| reason.to_string()),
+            consistency: Some(summary.consistency),
+            burst_wpm: Some(summary.burst_wpm),
+            slowest_words: summary
+                .slowest_words
+                .iter()
+                .map(|speed| speed.word.clone())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    pub fn with_chapter(mut self, chapter: &str) -> Self {
+        self.chapter = Some(chapter.to_string());
+        self
+    }
+
+    pub fn with_fingers(mut self, analytics: &FingerAnalytics) -> Self {
+        self.same_finger_bigrams = Some(analytics.same_finger_bigram_rate());
+        self.fingers = Some(analytics.to_field());
+        self
+    }
+
+    fn to_line(&self) -> String {
+        let mut fields = vec![
+            ("timestamp", self.timestamp.to_string()),
+            ("mode", self.mode.clone()),
+        ];
+        if let Some(source) = &self.source {
+            fields.push(("source", source.clone()));
+        }
+        if let Some(chapter) = &self.chapter {
+            fields.push(("chapter", chapter.clone()));
+        }
+        fields.extend([
+            ("wpm", format!("{:.2}", self.wpm)),
+            ("accuracy", format!("{:.2}", self.accuracy)),
+            ("duration", format!("{:.2}", self.duration)),
+            ("completed", self.completed.to_string()),
+        ]);
+        if let Some(fail_reason) = &self.fail_reason {
+            fields.push(("fail_reason", fail_reason.clone()));
+        }
+        if let Some(consistency) = self.consistency {
+            fields.push(("consistency", format!("{:.2}", consistency)));
+        }
+        if let Some(burst_wpm) = self.burst_wpm {
+            fields.push(("burst_wpm", format!("{:.2}", burst_wpm)));
+        }
+        if !self.slowest_words.is_empty() {
+            // target words never contain whitespace
+            fields.push(("slowest_words", self.slowest_words.join(" ")));
+        }
+        if let Some(same_finger_bigrams) = self.same_finger_bigrams {
+            fields.push(("same_finger_bigrams", format!("{:.2}", same_finger_bigrams)));
+        }
+        if let Some(fingers) = &self.fingers {
+            fields.push(("fingers", fingers.clone()));
+        }
+
+        fields
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value.replace(['\t', '\n'], " ")))
+            .join("\t")
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut entry = Self::default();
+        for (key, value) in line.split('\t').filter_map(|field| field.split_once('=')) {
+            match key {
+                "timestamp" => entry.timestamp = value.parse().ok()?,
+                "mode" => entry.mode = value.to_string(),
+                "source" => entry.source = Some(value.to_string()),
+                "chapter" => entry.chapter = Some(value.to_string()),
+                "wpm" => entry.wpm = value.parse().ok()?,
+                "accuracy" => entry.accuracy = value.parse().ok()?,
+                "duration" => entry.duration = value.parse().ok()?,
+                "completed" => entry.completed = value.parse().ok()?,
+                "fail_reason" => entry.fail_reason = Some(value.to_string()),
+                "consistency" => entry.consistency = Some(value.parse().ok()?),
+                "burst_wpm" => entry.burst_wpm = Some(value.parse().ok()?),
+                "same_finger_bigrams" => entry.same_finger_bigrams = Some(value.parse().ok()?),
+                "fingers" => entry.fingers = Some(value.to_string()),
+                "slowest_words" => {
+                    entry.slowest_words = value.split(' ').map(str::to_string).collect()
+                }
+                _ => {}
+            }
+        }
+
+        Some(entry)
+    }
+}
+
+pub fn append_entry(entry: &HistoryEntry) -> io::Result<()> {
+    append_line(HISTORY_FILE, &entry.to_line())
+}
+
+/// Every entry of the history, oldest first. Lines that can't be read are skipped
+pub fn read_entries() -> io::Result<Vec<HistoryEntry>> {
+    Ok(read_lines(HISTORY_FILE)?
+        .iter()
+        .filter_map(|line| HistoryEntry::from_line(line))
+        .collect())
+}
+
+/// Averages of several entries of the history
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HistoryAverages {
+    pub tests: usize,
+    pub wpm: f64,
+    pub accuracy: f64,
+    /// Average over the entries where consistency was measured
+    pub consistency: Option<f64>,
+}
+
+pub fn averages<'a>(entries: impl Iterator<Item = &'a HistoryEntry>) -> HistoryAverages {
+    let entries = entries.collect_vec();
+    if entries.is_empty() {
+        return HistoryAverages::default();
+    }
+
+    let tests = entries.len();
+    let consistencies = entries
+        .iter()
+        .filter_map(|entry| entry.consistency)
+        .collect_vec();
+    HistoryAverages {
+        tests,
+        wpm: entries.iter().map(|entry| entry.wpm).sum::<f64>() / tests as f64,
+        accuracy: entries.iter().map(|entry| entry.accuracy).sum::<f64>() / tests as f64,
+        consistency: (!consistencies.is_empty())
+            .then(|| consistencies.iter().sum::<f64>() / consistencies.len() as f64),
+    }
+}
+
+#[cfg(test)]
+mod history_entry_tests {
+    use super::HistoryEntry;
+
+    #[test]
+    fn entry_is_read_back() {
+        let entry = HistoryEntry {
+            timestamp: 10,
+            mode: "book".to_string(),
+            source: Some("/books/a.txt".to_string()),
+            chapter: Some("Chapter\t1".to_string()),
+            wpm: 60.5,
+            accuracy: 98.0,
+            duration: 30.25,
+            completed: false,
+            fail_reason: Some("a mistake was made".to_string()),
+            consistency: Some(75.5),
+            burst_wpm: Some(90.0),
+            slowest_words: vec!["the".to_string(), "quick".to_string()],
+            same_finger_bigrams: Some(4.5),
+            fingers: Some("li:50.0:100.0:0 ri:50.0:100.0:100".to_string()),
+        };
+
+        let result = HistoryEntry::from_line(&entry.to_line());
+
+        assert_eq!(
+            result,
+            Some(HistoryEntry {
+                chapter: Some("Chapter 1".to_string()),
+                ..entry
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let result = HistoryEntry::from_line("timestamp=1\tmode=words\tcolor=red\twpm=40");
+
+        assert_eq!(
+            result,
+            Some(HistoryEntry {
+                timestamp: 1,
+                mode: "words".to_string(),
+                wpm: 40.0,
+                ..Default::default()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod averages_tests {
+    use super::{averages, HistoryAverages, HistoryEntry};
+
+    #[test]
+    fn averages_of_entries() {
+        let entries = [
+            HistoryEntry {
+                wpm: 40.0,
+                accuracy: 90.0,
+                ..Default::default()
+            },
+            HistoryEntry {
+                wpm: 60.0,
+                accuracy: 100.0,
+                consistency: Some(80.0),
+                ..Default::default()
+            },
+        ];
+
+        let result = averages(entries.iter());
+
+        assert_eq!(
+            result,
+            HistoryAverages {
+                tests: 2,
+                wpm: 50.0,
+                accuracy: 95.0,
+                consistency: Some(80.0),
+            }
+        );
+    }
+
+    #[test]
+    fn averages_without_entries() {
+        let result = averages([].iter());
+
+        assert_eq!(result, HistoryAverages::default());
+    }
+}
@@ -0,0 +1,508 @@
+use std::{
+    fmt::Display,
+    fs::{read_to_string, write},
+    io::{self, ErrorKind},
+    path::Path,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::{builder::PossibleValue, ValueEnum};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use itertools::Itertools;
+
+use crate::{
+    failure::FailConditions,
+    transforms::Transform,
+    types::{Confidence, KeyEventSource, StopOnError, TypingOptions},
+    views::typing_playground::{ErrorDisplay, Overflow},
+};
+
+/// Where the time comes from, so it can be simulated
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Time of the system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Where the views read the keys pressed from
+pub trait InputSource {
+    /// Next key pressed, or `None` when no key was pressed within `timeout`
+    fn next_key(&mut self, timeout: Duration) -> io::Result<Option<KeyEventSource>>;
+
+    /// Clock the keys are timestamped with
+    fn clock(&self) -> &dyn Clock;
+}
+
+/// Keys pressed in the terminal
+#[derive(Debug, Default)]
+pub struct TerminalInput {
+    clock: SystemClock,
+}
+
+impl InputSource for TerminalInput {
+    fn next_key(&mut self, timeout: Duration) -> io::Result<Option<KeyEventSource>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                Ok(Some(KeyEventSource {
+                    key: key_event.code,
+                    modifiers: key_event.modifiers,
+                    timestamp: self.clock.now(),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &self.clock
+    }
+}
+
+/// Keys sent by another thread, such as a replay or a peer over the network. The input
+/// ends once the sender is dropped
+#[derive(Debug)]
+pub struct ChannelInput {
+    receiver: Receiver<KeyEventSource>,
+    clock: SystemClock,
+}
+
+impl ChannelInput {
+    pub fn new(receiver: Receiver<KeyEventSource>) -> Self {
+        Self {
+            receiver,
+            clock: SystemClock,
+        }
+    }
+}
+
+impl InputSource for ChannelInput {
+    fn next_key(&mut self, timeout: Duration) -> io::Result<Option<KeyEventSource>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "there are no more keys to read",
+            )),
+        }
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &self.clock
+    }
+}
+
+/// Target text of a test and the keys pressed to type it, saved to a file to watch the
+/// test again
+///
+/// The file starts with a `# target: ` line, the text escaped with `\n`, `\t` and `\\`,
+/// followed by `# layout: ` and `# options: ` lines telling how the keys were typed, failed
+/// and rendered. It
+/// continues with a line per key: the milliseconds since the first key, the modifiers and
+/// the key, separated by tabs
+///
+/// Keys are saved as pressed on the keyboard, before the layout translates them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub target: String,
+    pub events: Vec<KeyEventSource>,
+    /// Options the test was typed with
+    pub options: TypingOptions,
+    pub fail_conditions: FailConditions,
+    pub transforms: Vec<Transform>,
+    pub error_display: ErrorDisplay,
+    pub overflow: Overflow,
+    /// Name of the layout the test was typed with, when it wasn't QWERTY
+    pub layout: Option<String>,
+}
+
+impl Replay {
+    pub fn new(target: String, events: Vec<KeyEventSource>) -> Self {
+        Self {
+            target,
+            events,
+            options: TypingOptions::default(),
+            fail_conditions: FailConditions::default(),
+            transforms: vec![],
+            error_display: ErrorDisplay::default(),
+            overflow: Overflow::default(),
+            layout: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: TypingOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_fail_conditions(mut self, fail_conditions: FailConditions) -> Self {
+        self.fail_conditions = fail_conditions;
+        self
+    }
+
+    pub fn with_transforms(mut self, transforms: Vec<Transform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    pub fn with_error_display(mut self, error_display: ErrorDisplay) -> Self {
+        self.error_display = error_display;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: String) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Read a replay, with the first key pressed now
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_string());
+        let mut lines = content.lines().peekable();
+        let target = lines
+            .next()
+            .and_then(|line| line.strip_prefix("# target: "))
+            .ok_or_else(|| invalid("a replay has to start with its target text"))?;
+
+        let mut replay = Self::new(unescape(target), vec![]);
+        while let Some(line) = lines.next_if(|line| line.starts_with('#')) {
+            if let Some(name) = line.strip_prefix("# layout: ") {
+                replay.layout = Some(name.to_string());
+            } else if let Some(field) = line.strip_prefix("# options: ") {
+                parse_options(field, &mut replay)
+                    .ok_or_else(|| invalid(&format!("`{}` aren't typing options", field)))?;
+            }
+        }
+
+        let start = Instant::now();
+        replay.events = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (millis, modifiers, key) = line
+                    .splitn(3, '\t')
+                    .collect_tuple()
+                    .ok_or_else(|| invalid(&format!("`{}` isn't a key of a replay", line)))?;
+                Ok(KeyEventSource {
+                    key: parse_key(key)
+                        .ok_or_else(|| invalid(&format!("`{}` isn't a known key", key)))?,
+                    modifiers: KeyModifiers::from_bits_truncate(
+                        modifiers.parse().map_err(|_| invalid("wrong modifiers"))?,
+                    ),
+                    timestamp: start
+                        + Duration::from_millis(millis.parse().map_err(|_| invalid("wrong time"))?),
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(replay)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&read_to_string(path)?)
+    }
+
+    /// Write the replay, leaving out the keys that can't be replayed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write(path, self.to_string())
+    }
+
+    /// Send the keys at the pace they were typed, starting now
+    pub fn play(self) -> ChannelInput {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let Some(first) = self.events.first().map(|event| event.timestamp) else {
+                return;
+            };
+            let start = Instant::now();
+            for event in self.events {
+                let timestamp = start + event.timestamp.duration_since(first);
+                thread::sleep(timestamp.saturating_duration_since(Instant::now()));
+                let event = KeyEventSource {
+                    timestamp: Instant::now(),
+                    ..event
+                };
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+        });
+        ChannelInput::new(receiver)
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# target: {}", escape(&self.target))?;
+        if let Some(layout) = &self.layout {
+            writeln!(f, "# layout: {}", layout)?;
+        }
+        writeln!(f, "# options: {}", options_field(self))?;
+        let Some(first) = self.events.first() else {
+            return Ok(());
+        };
+        for event in &self.events {
+            let Some(key) = key_name(event.key) else {
+                continue;
+            };
+            writeln!(
+                f,
+                "{}\t{}\t{}",
+                event.timestamp.duration_since(first.timestamp).as_millis(),
+                event.modifiers.bits(),
+                key
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Options of the replay as `key=value` pairs separated by spaces, named after the flags
+/// setting them
+fn options_field(replay: &Replay) -> String {
+    let name = |value: Option<PossibleValue>| {
+        value.map_or(String::new(), |value| value.get_name().to_string())
+    };
+    let or_none = |value: Option<String>| value.unwrap_or("none".to_string());
+    let (options, fail_conditions) = (&replay.options, &replay.fail_conditions);
+    format!(
+        "skip-indent={} stop-on-error={} confidence={} max-extra-chars={} sudden-death={} \
+         min-accuracy={} min-wpm={} grace-period={} funbox={} errors={} overflow={}",
+        options.skip_indentation,
+        name(options.stop_on_error.to_possible_value()),
+        name(options.confidence.to_possible_value()),
+        or_none(options.max_extra_chars.map(|max| max.to_string())),
+        fail_conditions.sudden_death,
+        or_none(fail_conditions.min_accuracy.map(|min| min.to_string())),
+        or_none(fail_conditions.min_wpm.map(|min| min.to_string())),
+        fail_conditions.grace_period.as_secs(),
+        replay
+            .transforms
+            .iter()
+            .map(|transform| name(transform.to_possible_value()))
+            .join(","),
+        name(replay.error_display.to_possible_value()),
+        name(replay.overflow.to_possible_value()),
+    )
+}
+
+/// Read the options of an `options_field` into `replay`
+fn parse_options(field: &str, replay: &mut Replay) -> Option<()> {
+    let (options, fail_conditions) = (&mut replay.options, &mut replay.fail_conditions);
+    for pair in field.split_whitespace() {
+        let (key, value) = pair.split_once('=')?;
+        match key {
+            "skip-indent" => options.skip_indentation = value.parse().ok()?,
+            "stop-on-error" => options.stop_on_error = StopOnError::from_str(value, false).ok()?,
+            "confidence" => options.confidence = Confidence::from_str(value, false).ok()?,
+            "max-extra-chars" if value == "none" => options.max_extra_chars = None,
+            "max-extra-chars" => options.max_extra_chars = Some(value.parse().ok()?),
+            "sudden-death" => fail_conditions.sudden_death = value.parse().ok()?,
+            "min-accuracy" if value == "none" => fail_conditions.min_accuracy = None,
+            "min-accuracy" => fail_conditions.min_accuracy = Some(value.parse().ok()?),
+            "min-wpm" if value == "none" => fail_conditions.min_wpm = None,
+            "min-wpm" => fail_conditions.min_wpm = Some(value.parse().ok()?),
+            "grace-period" => {
+                fail_conditions.grace_period = Duration::from_secs(value.parse().ok()?)
+            }
+            "funbox" => {
+                replay.transforms = value
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| Transform::from_str(name, false).ok())
+                    .collect::<Option<_>>()?
+            }
+            "errors" => replay.error_display = ErrorDisplay::from_str(value, false).ok()?,
+            "overflow" => replay.overflow = Overflow::from_str(value, false).ok()?,
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+/// Name of the keys that can be replayed. Characters are named after themselves
+fn key_name(key: KeyCode) -> Option<String> {
+    match key {
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::Enter => Some("Enter".to_string()),
+        KeyCode::Tab => Some("Tab".to_string()),
+        KeyCode::Backspace => Some("Backspace".to_string()),
+        KeyCode::Esc => Some("Esc".to_string()),
+        _ => None,
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    match name {
+        "Enter" => Some(KeyCode::Enter),
+        "Tab" => Some(KeyCode::Tab),
+        "Backspace" => Some(KeyCode::Backspace),
+        "Esc" => Some(KeyCode::Esc),
+        _ => name.chars().exactly_one().ok().map(KeyCode::Char),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod input_tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::{
+        failure::FailConditions,
+        transforms::Transform,
+        types::{Confidence, StopOnError, TypingOptions},
+        views::{
+            test_harness::ScriptedEvents,
+            typing_playground::{ErrorDisplay, Overflow},
+        },
+    };
+
+    use super::{ChannelInput, InputSource, Replay};
+
+    #[test]
+    fn replays_are_written_a_key_per_line() {
+        let events = ScriptedEvents::new()
+            .type_at("a \n", &[0, 150, 300])
+            .press_at(KeyCode::Left, 400)
+            .events();
+        let replay = Replay::new("a b\n\\c".to_string(), events);
+
+        let result = replay.to_string();
+
+        assert_eq!(
+            result,
+            "# target: a b\\n\\\\c\n\
+             # options: skip-indent=false stop-on-error=off confidence=off max-extra-chars=none \
+             sudden-death=false min-accuracy=none min-wpm=none grace-period=0 funbox= \
+             errors=shown overflow=inline\n\
+             0\t0\ta\n150\t0\t \n300\t0\tEnter\n"
+        );
+    }
+
+    #[test]
+    fn replays_are_read_back() {
+        let result = Replay::parse("# target: a\\tb\n0\t2\ta\n120\t0\tBackspace\n").unwrap();
+
+        assert_eq!(result.target, "a\tb");
+        assert_eq!(result.events[0].key, KeyCode::Char('a'));
+        assert_eq!(result.events[0].modifiers, KeyModifiers::CONTROL);
+        assert_eq!(result.events[1].key, KeyCode::Backspace);
+        assert_eq!(
+            result.events[1]
+                .timestamp
+                .duration_since(result.events[0].timestamp),
+            Duration::from_millis(120)
+        );
+    }
+
+    #[test]
+    fn layout_and_options_are_read_back() {
+        let options = TypingOptions {
+            skip_indentation: true,
+            stop_on_error: StopOnError::Word,
+            confidence: Confidence::Max,
+            max_extra_chars: Some(3),
+            zen: false,
+        };
+        let fail_conditions = FailConditions {
+            sudden_death: true,
+            min_accuracy: Some(92.5),
+            min_wpm: None,
+            grace_period: Duration::from_secs(3),
+        };
+        let replay = Replay::new("a".to_string(), vec![])
+            .with_options(options.clone())
+            .with_fail_conditions(fail_conditions.clone())
+            .with_transforms(vec![Transform::Mirror, Transform::ReadAhead])
+            .with_error_display(ErrorDisplay::Blind)
+            .with_overflow(Overflow::Compact)
+            .with_layout("dvorak".to_string());
+
+        let result = Replay::parse(&replay.to_string()).unwrap();
+
+        assert_eq!(result, replay);
+    }
+
+    #[test]
+    fn replays_need_a_target() {
+        let result = Replay::parse("0\t0\ta\n");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn channel_input_ends_with_its_sender() {
+        let (sender, receiver) = mpsc::channel();
+        let mut input = ChannelInput::new(receiver);
+
+        for event in ScriptedEvents::new().type_text("a").events() {
+            sender.send(event).unwrap();
+        }
+        let key = input.next_key(Duration::ZERO).unwrap();
+        let timeout = input.next_key(Duration::ZERO).unwrap();
+        drop(sender);
+        let end = input.next_key(Duration::ZERO);
+
+        assert_eq!(key.map(|event| event.key), Some(KeyCode::Char('a')));
+        assert_eq!(timeout, None);
+        assert!(end.is_err());
+    }
+
+    #[test]
+    fn replays_are_played_in_order() {
+        let events = ScriptedEvents::new().type_at("ab", &[0, 20]).events();
+        let replay = Replay::new("ab".to_string(), events);
+
+        let mut input = replay.play();
+        let keys = (0..2)
+            .filter_map(|_| input.next_key(Duration::from_secs(1)).unwrap())
+            .map(|event| event.key)
+            .collect::<Vec<_>>();
+
+        assert_eq!(keys, vec![KeyCode::Char('a'), KeyCode::Char('b')]);
+        assert!(input.next_key(Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::Itertools;
+
+use crate::storage::data_dir;
+
+/// Keyboard layout typed on QWERTY hardware
+///
+/// Layouts are text files where every row of keys takes two lines, the characters typed
+/// without Shift and then with Shift, in the order of the keys of a QWERTY keyboard. Lines
+/// starting with `#` are comments.
+///
+/// Keys are pressed by the fingers of touch typing on QWERTY, unless the file ends with a
+/// finger row per row of keys: `fingers ` followed by the [Finger] of every key, as its
+/// number from 0 for the left pinky to 8 for the right pinky
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub name: String,
+    rows: Vec<KeyRow>,
+    /// Character typed with this layout by the key typing a character on QWERTY
+    remapping: HashMap<char, char>,
+}
+
+/// A row of keys, from left to right
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRow {
+    pub unshifted: Vec<char>,
+    pub shifted: Vec<char>,
+    /// Finger pressing every key, empty when the layout doesn't assign them
+    fingers: Vec<Finger>,
+}
+
+/// Start of the lines assigning the keys of a row to fingers
+const FINGERS_PREFIX: &str = "fingers ";
+
+/// Where a character is typed on the keyboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPosition {
+    pub row: usize,
+    pub column: usize,
+    pub shifted: bool,
+}
+
+/// Finger pressing a key when touch typing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    Thumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    /// Finger pressing the key at `column` of `row`, the number row being the first one.
+    /// Keys right of the home position of the right pinky are pressed by it too
+    pub fn at(row: usize, column: usize) -> Self {
+        // the number row is shifted one key to the left
+        let column = if row == 0 {
+            column.saturating_sub(1)
+        } else {
+            column
+        };
+
+        match column {
+            0 => Self::LeftPinky,
+            1 => Self::LeftRing,
+            2 => Self::LeftMiddle,
+            3 | 4 => Self::LeftIndex,
+            5 | 6 => Self::RightIndex,
+            7 => Self::RightMiddle,
+            8 => Self::RightRing,
+            _ => Self::RightPinky,
+        }
+    }
+
+    /// Finger pressing the key typing `c`, whitespace included
+    pub fn for_char(layout: &Layout, c: char) -> Option<Self> {
+        match c {
+            ' ' => Some(Self::Thumb),
+            '\t' => Some(Self::LeftPinky),
+            '\n' => Some(Self::RightPinky),
+            _ => layout
+                .position(c)
+                .map(|position| layout.finger(position.row, position.column)),
+        }
+    }
+
+    /// Finger numbered `digit` in the finger rows of a layout
+    fn from_digit(digit: char) -> Option<Self> {
+        let fingers = [
+            Self::LeftPinky,
+            Self::LeftRing,
+            Self::LeftMiddle,
+            Self::LeftIndex,
+            Self::Thumb,
+            Self::RightIndex,
+            Self::RightMiddle,
+            Self::RightRing,
+            Self::RightPinky,
+        ];
+        fingers.get(digit.to_digit(10)? as usize).copied()
+    }
+
+    pub fn is_left_hand(&self) -> bool {
+        *self < Self::Thumb
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LeftPinky => "left pinky",
+            Self::LeftRing => "left ring",
+            Self::LeftMiddle => "left middle",
+            Self::LeftIndex => "left index",
+            Self::Thumb => "thumbs",
+            Self::RightIndex => "right index",
+            Self::RightMiddle => "right middle",
+            Self::RightRing => "right ring",
+            Self::RightPinky => "right pinky",
+        }
+    }
+
+    /// Name of the finger in the history
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Self::LeftPinky => "lp",
+            Self::LeftRing => "lr",
+            Self::LeftMiddle => "lm",
+            Self::LeftIndex => "li",
+            Self::Thumb => "th",
+            Self::RightIndex => "ri",
+            Self::RightMiddle => "rm",
+            Self::RightRing => "rr",
+            Self::RightPinky => "rp",
+        }
+    }
+}
+
+impl Layout {
+    pub fn qwerty() -> Self {
+        Self::parse("qwerty", Self::bundled("qwerty").unwrap()).unwrap()
+    }
+
+    /// Definition of a layout shipped within the binary
+    fn bundled(name: &str) -> Option<&'static str> {
+        match name.to_lowercase().as_str() {
+            "qwerty" => Some(include_str!("../layouts/qwerty.txt")),
+            "dvorak" => Some(include_str!("../layouts/dvorak.txt")),
+            "colemak" => Some(include_str!("../layouts/colemak.txt")),
+            "colemak-dh" | "colemakdh" => Some(include_str!("../layouts/colemak-dh.txt")),
+            "workman" => Some(include_str!("../layouts/workman.txt")),
+            "azerty" => Some(include_str!("../layouts/azerty.txt")),
+            "qwertz" => Some(include_str!("../layouts/qwertz.txt")),
+            _ => None,
+        }
+    }
+
+    pub fn parse(name: &str, definition: &str) -> io::Result<Self> {
+        let (finger_lines, lines): (Vec<_>, Vec<_>) = definition
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.is_empty())
+            .partition(|line| line.starts_with(FINGERS_PREFIX));
+        let lines = lines
+            .into_iter()
+            .map(|line| line.chars().collect_vec())
+            .collect_vec();
+        let mut rows = lines
+            .chunks(2)
+            .map(|row| KeyRow {
+                unshifted: row[0].clone(),
+                shifted: row.get(1).cloned().unwrap_or_default(),
+                fingers: Vec::new(),
+            })
+            .collect_vec();
+
+        let qwerty_rows = if name == "qwerty" {
+            rows.clone()
+        } else {
+            Self::qwerty().rows
+        };
+        let has_qwerty_shape = rows.len() == qwerty_rows.len()
+            && rows.iter().zip(&qwerty_rows).all(|(row, qwerty_row)| {
+                row.unshifted.len() == qwerty_row.unshifted.len()
+                    && row.shifted.len() == qwerty_row.shifted.len()
+            });
+        if !has_qwerty_shape {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "layout `{}` doesn't have the keys of a QWERTY keyboard, row by row",
+                    name
+                ),
+            ));
+        }
+
+        if !finger_lines.is_empty() {
+            let finger_rows = finger_lines
+                .iter()
+                .map(|line| {
+                    line[FINGERS_PREFIX.len()..]
+                        .chars()
+                        .map(Finger::from_digit)
+                        .collect::<Option<Vec<_>>>()
+                })
+                .collect::<Option<Vec<_>>>();
+            let finger_rows = finger_rows
+                .filter(|finger_rows| {
+                    finger_rows.len() == rows.len()
+                        && finger_rows
+                            .iter()
+                            .zip(&rows)
+                            .all(|(fingers, row)| fingers.len() == row.unshifted.len())
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "layout `{}` has to give a finger from 0 to 8 to every key, row by row",
+                            name
+                        ),
+                    )
+                })?;
+            for (row, fingers) in rows.iter_mut().zip(finger_rows) {
+                row.fingers = fingers;
+            }
+        }
+
+        let remapping = qwerty_rows
+            .iter()
+            .zip(&rows)
+            .flat_map(|(qwerty_row, row)| {
+                let unshifted = qwerty_row.unshifted.iter().zip(&row.unshifted);
+                let shifted = qwerty_row.shifted.iter().zip(&row.shifted);
+                unshifted.chain(shifted).map(|(&from, &to)| (from, to))
+            })
+            .collect();
+
+        Ok(Self {
+            name: name.to_string(),
+            rows,
+            remapping,
+        })
+    }
+
+    pub fn rows(&self) -> &[KeyRow] {
+        &self.rows
+    }
+
+    /// Finger pressing the key at `column` of `row`, the one of touch typing unless the layout
+    /// assigns another one
+    pub fn finger(&self, row: usize, column: usize) -> Finger {
+        self.rows
+            .get(row)
+            .and_then(|keys| keys.fingers.get(column))
+            .copied()
+            .unwrap_or_else(|| Finger::at(row, column))
+    }
+
+    /// Key typing `c` with this layout
+    pub fn position(&self, c: char) -> Option<KeyPosition> {
+        self.rows.iter().enumerate().find_map(|(row, keys)| {
+            let unshifted = keys.unshifted.iter().position(|&key| key == c);
+            let shifted = keys.shifted.iter().position(|&key| key == c);
+            unshifted
+                .map(|column| (column, false))
+                .or(shifted.map(|column| (column, true)))
+                .map(|(column, shifted)| KeyPosition {
+                    row,
+                    column,
+                    shifted,
+                })
+        })
+    }
+
+    /// Character typed with this layout by the key that types `c` on QWERTY
+    pub fn remap(&self, c: char) -> char {
+        self.remapping.get(&c).copied().unwrap_or(c)
+    }
+
+    /// Key pressed with this layout. Shortcuts, with Ctrl or Alt, keep their QWERTY key
+    pub fn remap_key(&self, key: KeyCode, modifiers: KeyModifiers) -> KeyCode {
+        match key {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                KeyCode::Char(self.remap(c))
+            }
+            _ => key,
+        }
+    }
+}
+
+/// Load a layout, either a bundled one by name, a layout file or the name of a file in the
+/// `layouts` directory of the data directory
+pub fn load_layout(source: &str) -> io::Result<Layout> {
+    if let Some(definition) = Layout::bundled(source) {
+        return Layout::parse(&source.to_lowercase(), definition);
+    }
+
+    let user_path = data_dir().join("layouts").join(format!("{}.txt", source));
+    let path = [Path::new(source), user_path.as_path()]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("`{}` is neither a layout file nor a known layout", source),
+            )
+        })?;
+    let name = path.file_stem().map_or(source.to_string(), |stem| {
+        stem.to_string_lossy().to_string()
+    });
+
+    Layout::parse(&name, &read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod layout_tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use super::{Finger, Layout};
+
+    #[test]
+    fn bundled_layouts_have_qwerty_shape() {
+        for name in [
+            "qwerty",
+            "dvorak",
+            "colemak",
+            "colemak-dh",
+            "workman",
+            "azerty",
+            "qwertz",
+        ] {
+            let result = Layout::parse(name, Layout::bundled(name).unwrap());
+
+            assert!(result.is_ok(), "{} can't be parsed", name);
+        }
+    }
+
+    #[test]
+    fn keys_are_remapped() {
+        let layout = Layout::parse("dvorak", Layout::bundled("dvorak").unwrap()).unwrap();
+
+        let result = "Hello, world!"
+            .chars()
+            .map(|c| layout.remap(c))
+            .collect::<String>();
+
+        assert_eq!(result, "D.nnrw ,rpne!");
+    }
+
+    #[test]
+    fn qwerty_is_unchanged() {
+        let layout = Layout::qwerty();
+
+        let result = "Hello, world!"
+            .chars()
+            .map(|c| layout.remap(c))
+            .collect::<String>();
+
+        assert_eq!(result, "Hello, world!");
+    }
+
+    #[test]
+    fn shortcuts_are_not_remapped() {
+        let layout = Layout::parse("dvorak", Layout::bundled("dvorak").unwrap()).unwrap();
+
+        let typed = layout.remap_key(KeyCode::Char('w'), KeyModifiers::SHIFT);
+        let shortcut = layout.remap_key(KeyCode::Char('w'), KeyModifiers::CONTROL);
+
+        assert_eq!(typed, KeyCode::Char(','));
+        assert_eq!(shortcut, KeyCode::Char('w'));
+    }
+
+    #[test]
+    fn fingers_follow_the_layout() {
+        let layout = Layout::parse("dvorak", Layout::bundled("dvorak").unwrap()).unwrap();
+
+        let result = "aeu hsS\n1"
+            .chars()
+            .map(|c| Finger::for_char(&layout, c))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            result,
+            [
+                Finger::LeftPinky,
+                Finger::LeftMiddle,
+                Finger::LeftIndex,
+                Finger::Thumb,
+                Finger::RightIndex,
+                Finger::RightPinky,
+                Finger::RightPinky,
+                Finger::RightPinky,
+                Finger::LeftPinky,
+            ]
+            .map(Some)
+        );
+    }
+
+    #[test]
+    fn fingers_are_read_from_the_layout() {
+        let definition = format!(
+            "{}\nfingers 0012334556788\nfingers 0123555678888\nfingers 01233556788\nfingers 1233556788",
+            Layout::bundled("qwerty").unwrap()
+        );
+
+        let layout = Layout::parse("custom", &definition).unwrap();
+
+        assert_eq!(Finger::for_char(&layout, 'q'), Some(Finger::LeftPinky));
+        assert_eq!(Finger::for_char(&layout, 'T'), Some(Finger::RightIndex));
+        assert_eq!(Finger::for_char(&layout, 'z'), Some(Finger::LeftRing));
+        assert_eq!(Finger::for_char(&layout, ' '), Some(Finger::Thumb));
+    }
+
+    #[test]
+    fn layout_with_missing_fingers() {
+        let definition = format!(
+            "{}\nfingers 0012334556788\nfingers 01233",
+            Layout::bundled("qwerty").unwrap()
+        );
+
+        let result = Layout::parse("custom", &definition);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn layout_with_missing_keys() {
+        let result = Layout::parse("broken", "abc\nABC\n");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use std::{fmt::Display, io};
+
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    layout::Layout,
+    metrics::RunSummary,
+    storage::{read_key_values, write_key_values},
+};
+
+const LESSONS_FILE: &str = "lessons.tsv";
+
+/// Speed needed to pass a lesson, in words per minute
+const PASS_WPM: f64 = 20.0;
+
+/// Accuracy needed to pass a lesson, in percentage
+const PASS_ACCURACY: f64 = 95.0;
+
+/// Dictionary words needed to make a lesson out of them instead of generated words
+const MIN_DICTIONARY_WORDS: usize = 10;
+
+/// A lesson of the touch typing course, introducing a few keys
+///
+/// Keys are given by their position on the keyboard, as `(row, column)` with the number
+/// row first, so the course follows the layout being learnt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lesson {
+    pub title: &'static str,
+    keys: &'static [(usize, usize)],
+}
+
+/// Lessons of the course, in order. Every lesson also practices the keys of the previous ones
+pub const LESSONS: [Lesson; 15] = [
+    Lesson {
+        title: "Home row, index fingers",
+        keys: &[(2, 3), (2, 6)],
+    },
+    Lesson {
+        title: "Home row, middle fingers",
+        keys: &[(2, 2), (2, 7)],
+    },
+    Lesson {
+        title: "Home row, ring fingers",
+        keys: &[(2, 1), (2, 8)],
+    },
+    Lesson {
+        title: "Home row, pinkies",
+        keys: &[(2, 0), (2, 9)],
+    },
+    Lesson {
+        title: "Home row, inner keys",
+        keys: &[(2, 4), (2, 5)],
+    },
+    Lesson {
+        title: "Top row, middle fingers",
+        keys: &[(1, 2), (1, 7)],
+    },
+    Lesson {
+        title: "Top row, index fingers",
+        keys: &[(1, 3), (1, 6)],
+    },
+    Lesson {
+        title: "Top row, inner keys",
+        keys: &[(1, 4), (1, 5)],
+    },
+    Lesson {
+        title: "Top row, ring fingers",
+        keys: &[(1, 1), (1, 8)],
+    },
+    Lesson {
+        title: "Top row, pinkies",
+        keys: &[(1, 0), (1, 9)],
+    },
+    Lesson {
+        title: "Bottom row, index fingers",
+        keys: &[(3, 3), (3, 6)],
+    },
+    Lesson {
+        title: "Bottom row, middle fingers",
+        keys: &[(3, 2), (3, 7)],
+    },
+    Lesson {
+        title: "Bottom row, inner keys",
+        keys: &[(3, 4), (3, 5)],
+    },
+    Lesson {
+        title: "Bottom row, ring fingers and pinkies",
+        keys: &[(3, 0), (3, 1), (3, 8), (3, 9)],
+    },
+    Lesson {
+        title: "Number row",
+        keys: &[
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (0, 6),
+            (0, 7),
+            (0, 8),
+            (0, 9),
+            (0, 10),
+        ],
+    },
+];
+
+impl Lesson {
+    /// Characters introduced by the lesson with `layout`
+    pub fn new_chars(&self, layout: &Layout) -> Vec<char> {
+        self.keys
+            .iter()
+            .filter_map(|&(row, column)| layout.rows().get(row)?.unshifted.get(column).copied())
+            .collect()
+    }
+}
+
+/// Outcome of a test of a lesson
+#[derive(Debug, Clone, PartialEq)]
+pub struct LessonResult {
+    /// Index of the lesson in [LESSONS]
+    pub index: usize,
+    pub passed: bool,
+}
+
+impl LessonResult {
+    /// A lesson is passed when its text is completed fast and accurately enough
+    pub fn new(index: usize, summary: &RunSummary) -> Self {
+        Self {
+            index,
+            passed: summary.completed
+                && summary.wpm >= PASS_WPM
+                && summary.accuracy >= PASS_ACCURACY,
+        }
+    }
+}
+
+impl Display for LessonResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Lesson {}, {}: ",
+            self.index + 1,
+            LESSONS[self.index].title
+        )?;
+        match (self.passed, LESSONS.get(self.index + 1)) {
+            (true, Some(next)) => write!(f, "passed, next is {}", next.title),
+            (true, None) => write!(f, "passed, the course is completed"),
+            (false, _) => write!(
+                f,
+                "not passed yet, it takes {} wpm and {}% accuracy",
+                PASS_WPM, PASS_ACCURACY
+            ),
+        }
+    }
+}
+
+/// Characters unlocked once the lesson at `index` is reached, its own included
+pub fn unlocked_chars(index: usize, layout: &Layout) -> Vec<char> {
+    LESSONS[..=index]
+        .iter()
+        .flat_map(|lesson| lesson.new_chars(layout))
+        .collect()
+}
+
+/// Words for the lesson at `index`: dictionary words typed with the unlocked characters
+/// when there are enough of them, generated words otherwise
+///
+/// Every word contains at least one of the characters introduced by the lesson
+pub fn pick_lesson_words(
+    index: usize,
+    layout: &Layout,
+    dictionary: &[&str],
+    quantity: usize,
+) -> Vec<String> {
+    let allowed_chars = unlocked_chars(index, layout);
+    let new_chars = LESSONS[index].new_chars(layout);
+    let practices_lesson = |word: &str| word.chars().any(|c| new_chars.contains(&c));
+
+    let words = dictionary
+        .iter()
+        .filter(|word| word.chars().all(|c| allowed_chars.contains(&c)))
+        .filter(|word| practices_lesson(word))
+        .collect_vec();
+
+    let mut rng = rand::thread_rng();
+    if words.len() >= MIN_DICTIONARY_WORDS {
+        return (0..quantity)
+            .filter_map(|_| words.choose(&mut rng).map(|word| word.to_string()))
+            .collect();
+    }
+
+    (0..quantity)
+        .filter_map(|_| {
+            let length = rng.gen_range(2..=5);
+            let mut word = vec![*new_chars.choose(&mut rng)?];
+            word.extend((1..length).filter_map(|_| allowed_chars.choose(&mut rng)));
+            word.shuffle(&mut rng);
+            Some(word.into_iter().collect())
+        })
+        .collect()
+}
+
+/// Number of lessons passed with `layout`
+pub fn saved_progress(layout: &Layout) -> io::Result<usize> {
+    let progress = read_key_values(LESSONS_FILE)?;
+    Ok(progress
+        .get(&layout.name)
+        .and_then(|passed| passed.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(LESSONS.len()))
+}
+
+pub fn save_progress(layout: &Layout, passed: usize) -> io::Result<()> {
+    let mut progress = read_key_values(LESSONS_FILE)?;
+    progress.insert(layout.name.clone(), passed.to_string());
+    write_key_values(LESSONS_FILE, &progress)
+}
+
+#[cfg(test)]
+mod lessons_tests {
+    use crate::layout::{load_layout, Layout};
+
+    use super::{pick_lesson_words, unlocked_chars, LESSONS};
+
+    #[test]
+    fn lessons_follow_the_layout() {
+        let qwerty = Layout::qwerty();
+        let colemak = load_layout("colemak").unwrap();
+
+        assert_eq!(LESSONS[1].new_chars(&qwerty), vec!['d', 'k']);
+        assert_eq!(LESSONS[1].new_chars(&colemak), vec!['s', 'e']);
+    }
+
+    #[test]
+    fn chars_of_previous_lessons_are_unlocked() {
+        let result = unlocked_chars(2, &Layout::qwerty());
+
+        assert_eq!(result, vec!['f', 'j', 'd', 'k', 's', 'l']);
+    }
+
+    #[test]
+    fn words_only_use_unlocked_chars() {
+        let allowed_chars = unlocked_chars(2, &Layout::qwerty());
+
+        let result = pick_lesson_words(2, &Layout::qwerty(), &["sold", "kids"], 20);
+
+        assert_eq!(result.len(), 20);
+        assert!(result
+            .iter()
+            .all(|word| word.chars().all(|c| allowed_chars.contains(&c))));
+        assert!(result.iter().all(|word| word.contains(['s', 'l'])));
+    }
+
+    #[test]
+    fn dictionary_words_are_used_when_there_are_enough() {
+        let dictionary = [
+            "fad", "dad", "ask", "lad", "sad", "add", "all", "fall", "falls", "salad", "lass",
+        ];
+
+        let result = pick_lesson_words(3, &Layout::qwerty(), &dictionary, 20);
+
+        assert!(result
+            .iter()
+            .all(|word| dictionary.contains(&word.as_str())));
+    }
+}
@@ -1,48 +1,909 @@
-use clap::Parser;
-use sentences::pick_random_words_from_dictionary;
+use book::{Book, BookProgress};
+use clap::{Parser, Subcommand};
+use code::load_snippet;
+use custom_text::CustomText;
+use dictionary::{find_language, list_dictionaries, Dictionary, Direction};
+use drill::{is_auto, parse_ngram, pick_drill_words, slowest_ngrams, DrillStyle};
+use engine::TypingEngine;
+use error::AppError;
+use failure::FailConditions;
+use fingers::FingerAnalytics;
+use generators::{generate_text, Generator};
+use history::{append_entry, averages, read_entries, HistoryEntry};
+use input::{InputSource, Replay, TerminalInput};
+use itertools::Itertools;
+use layout::{load_layout, Layout};
+use lessons::{pick_lesson_words, save_progress, saved_progress, LessonResult, LESSONS};
+use metrics::RunSummary;
+use ngrams::NgramTimings;
+use rand::Rng;
 use std::{
-    fs::read_to_string,
-    io::{self},
+    fs,
+    io::{self, ErrorKind, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+use storage::data_dir;
+use transforms::{apply_transforms, Transform};
+use types::{Confidence, KeyEventSource, StopOnError, TypingOptions};
+use views::{
+    run::Runnable,
+    statistics::StatisticsView,
+    typing_playground::{ErrorDisplay, Overflow, TypingPlayground},
 };
-use views::{run::Runnable, statistics::StatisticsView, typing_playground::TypingPlayground};
 
+mod book;
+mod code;
+mod custom_text;
+mod dictionary;
+mod drill;
+mod engine;
+mod error;
+mod failure;
+mod fingers;
+mod generators;
+mod history;
+mod input;
+mod layout;
+mod lessons;
+mod metrics;
+mod ngrams;
 mod sentences;
 mod splitter;
+mod storage;
+mod transforms;
 mod tui;
 mod types;
 mod views;
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
     #[arg(short, long, default_value = "./dictionaries/default.txt")]
     dictionary_dir: String,
+    /// Pick the words from the dictionary of this language, such as `en` or `es`. It's
+    /// looked for next to the default dictionary and in the data directory
+    #[arg(long, value_name = "CODE")]
+    language: Option<String>,
     #[arg(short, long, default_value = "25")]
     total_words: usize,
+    /// Type a code snippet instead of words. Either a source file or a language name
+    #[arg(long, value_name = "FILE|LANGUAGE")]
+    code: Option<String>,
+    /// Fill the indentation of new lines automatically
+    #[arg(long)]
+    skip_indent: bool,
+    /// Don't accept wrong letters, or don't let you move past a wrong word
+    #[arg(long, value_enum, default_value_t = StopOnError::Off)]
+    stop_on_error: StopOnError,
+    /// Don't let backspace go back to previous words (on) or disable it entirely (max)
+    #[arg(long, value_enum, default_value_t = Confidence::Off)]
+    confidence: Confidence,
+    /// Maximum number of characters that can be typed past the end of a word
+    #[arg(long, value_name = "N")]
+    max_extra_chars: Option<usize>,
+    /// Render characters typed past the end of a word in the text or compactly
+    #[arg(long, value_enum, default_value_t = Overflow::Inline)]
+    overflow: Overflow,
+    /// Show mistakes while typing, hide them until the results, or render the text as if
+    /// it was typed correctly
+    #[arg(long, value_enum, default_value_t = ErrorDisplay::Shown)]
+    errors: ErrorDisplay,
+    /// Fail the test on the first mistake
+    #[arg(long)]
+    sudden_death: bool,
+    /// Fail the test when the accuracy drops below this percentage
+    #[arg(long, value_name = "PERCENT")]
+    min_accuracy: Option<f64>,
+    /// Fail the test when the speed drops below this many words per minute
+    #[arg(long, value_name = "WPM")]
+    min_wpm: Option<f64>,
+    /// Seconds since the first key before checking the minimum accuracy and speed
+    #[arg(long, value_name = "SECONDS", default_value = "5")]
+    grace_period: u64,
+    /// Type a custom text instead of words. Either a file or `-` to read the standard input
+    #[arg(long, value_name = "FILE|-", conflicts_with = "code")]
+    text: Option<String>,
+    /// Type a book test by test, continuing from its bookmark
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["code", "text"])]
+    book: Option<String>,
+    /// Split the custom text or the book in tests of this many words
+    #[arg(long, value_name = "N")]
+    chunk_words: Option<usize>,
+    /// Continue the custom text from the chunk where the last test stopped
+    #[arg(long, requires = "text")]
+    resume: bool,
+    /// Type a random chunk of the custom text instead of following its order
+    #[arg(long, requires = "text", conflicts_with = "resume")]
+    shuffle: bool,
+    /// Type with this layout on a QWERTY keyboard. Either a layout name or a layout file
+    #[arg(long, global = true, value_name = "LAYOUT|FILE", value_parser = load_layout)]
+    layout: Option<Layout>,
+    /// Take the next lesson of the touch typing course, or lesson N
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        value_parser = clap::value_parser!(u64).range(1..=LESSONS.len() as u64),
+        conflicts_with_all = ["code", "text", "book", "ngrams"]
+    )]
+    lesson: Option<Option<u64>>,
+    /// Show a keyboard under the text with the next key to press
+    #[arg(long)]
+    keyboard: bool,
+    /// Drill these n-grams, or the slowest bigrams of the history with `auto` given alone
+    #[arg(
+        long,
+        value_name = "NGRAMS|auto",
+        value_delimiter = ',',
+        value_parser = parse_ngram,
+        conflicts_with_all = ["code", "text", "book"]
+    )]
+    ngrams: Option<Vec<String>>,
+    /// Type generated numbers, code symbols, identifiers or shell commands instead of words
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KIND",
+        conflicts_with_all = ["code", "text", "book", "ngrams", "lesson"]
+    )]
+    generate: Option<Generator>,
+    /// Transform the words, numbers and symbols of the test, or hide them. Transforms are
+    /// applied in order. Only word, drill and generated tests take them: code, texts and
+    /// books keep the text given, lessons the keys they unlock, and zen has no text
+    #[arg(
+        long,
+        value_enum,
+        value_name = "TRANSFORMS",
+        value_delimiter = ',',
+        conflicts_with_all = ["code", "text", "book", "lesson", "zen"]
+    )]
+    funbox: Vec<Transform>,
+    /// Save the keys of the test to this file, to watch it again with `--replay`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["book", "zen"])]
+    record: Option<PathBuf>,
+    /// Watch a test saved with `--record`, typed again at the pace it was typed, with the
+    /// layout, typing options, fail conditions, funbox and display it was recorded with
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "code", "text", "book", "ngrams", "lesson", "generate", "zen", "record", "layout",
+            "skip_indent", "stop_on_error", "confidence", "max_extra_chars", "sudden_death",
+            "min_accuracy", "min_wpm", "grace_period", "funbox", "errors", "overflow"
+        ]
+    )]
+    replay: Option<PathBuf>,
+    /// Type freely without a target text until Esc is pressed, to measure your natural speed
+    #[arg(
+        long,
+        conflicts_with_all = ["code", "text", "book", "ngrams", "lesson", "generate"]
+    )]
+    zen: bool,
+    /// Drill dictionary words containing the n-grams or the n-grams themselves
+    #[arg(long, value_enum, default_value_t = DrillStyle::Words, requires = "ngrams")]
+    drill_style: DrillStyle,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum Command {
+    /// Manage dictionaries
+    Dictionary {
+        #[command(subcommand)]
+        dictionary: DictionaryCommand,
+    },
+    /// List the lessons of the touch typing course and the ones passed with the layout
+    Lessons,
+    /// Show statistics of every test typed so far
+    Stats {
+        #[command(subcommand)]
+        stats: StatsCommand,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DictionaryCommand {
+    /// List the dictionaries available with `--language`
+    List,
+    /// Report duplicates, blank lines, whitespace, invisible characters, mixed scripts and
+    /// CRLF line endings in a dictionary file
+    Check { file: PathBuf },
+    /// Write a cleaned copy of a dictionary file, with its words in Unicode NFC form
+    Normalize {
+        file: PathBuf,
+        /// File to write the copy to, instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum StatsCommand {
+    /// List the bigrams with the slowest average time
+    Bigrams {
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+    /// List the trigrams with the slowest average time
+    Trigrams {
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+}
+
+impl Args {
+    fn typing_options(&self) -> TypingOptions {
+        TypingOptions {
+            skip_indentation: self.skip_indent,
+            stop_on_error: self.stop_on_error,
+            confidence: self.confidence,
+            max_extra_chars: self.max_extra_chars,
+            zen: self.zen,
+        }
+    }
+
+    fn fail_conditions(&self) -> FailConditions {
+        FailConditions {
+            sudden_death: self.sudden_death,
+            min_accuracy: self.min_accuracy,
+            min_wpm: self.min_wpm,
+            grace_period: Duration::from_secs(self.grace_period),
+        }
+    }
+
+    /// Directories where dictionaries are looked for by language, the ones of the user first
+    fn dictionary_dirs(&self) -> Vec<PathBuf> {
+        let bundled_dir = Path::new(&self.dictionary_dir)
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        vec![data_dir().join("dictionaries"), bundled_dir]
+    }
+
+    /// Dictionary of `--language`, or the one of `--dictionary-dir`
+    fn dictionary(&self) -> Result<Dictionary, AppError> {
+        let source = self.dictionary_source();
+        let dictionary = match &self.language {
+            Some(language) => find_language(language, &self.dictionary_dirs()),
+            None => Dictionary::load(&self.dictionary_dir),
+        }
+        .map_err(AppError::dictionary(&source))?;
+
+        if dictionary.words.is_empty() {
+            return Err(AppError::EmptyDictionary(source));
+        }
+        Ok(dictionary)
+    }
+
+    /// Where the words come from, for the history
+    fn dictionary_source(&self) -> String {
+        self.language
+            .clone()
+            .unwrap_or_else(|| self.dictionary_dir.clone())
+    }
+
+    /// Layout typed, QWERTY unless another one is given
+    fn keyboard_layout(&self) -> Layout {
+        self.layout.clone().unwrap_or_else(Layout::qwerty)
+    }
+
+    /// Generated text with the transforms of `--funbox` applied
+    fn transformed(&self, text: String) -> String {
+        apply_transforms(&text, &self.funbox)
+    }
+
+    /// Options of the command line with the layout, typing options, fail conditions, funbox
+    /// and display `replay` was recorded with
+    fn replaying(&self, replay: &Replay) -> Result<Self, AppError> {
+        let layout = replay
+            .layout
+            .as_deref()
+            .map(load_layout)
+            .transpose()
+            .map_err(AppError::config("--replay"))?;
+
+        Ok(Self {
+            layout,
+            skip_indent: replay.options.skip_indentation,
+            stop_on_error: replay.options.stop_on_error,
+            confidence: replay.options.confidence,
+            max_extra_chars: replay.options.max_extra_chars,
+            sudden_death: replay.fail_conditions.sudden_death,
+            min_accuracy: replay.fail_conditions.min_accuracy,
+            min_wpm: replay.fail_conditions.min_wpm,
+            grace_period: replay.fail_conditions.grace_period.as_secs(),
+            funbox: replay.transforms.clone(),
+            errors: replay.error_display,
+            overflow: replay.overflow,
+            ..self.clone()
+        })
+    }
+
+    /// Typing playground for `target_word` with every option of the command line
+    fn typing_playground(&self, target_word: String) -> TypingPlayground {
+        let typing_playground = TypingPlayground::new(target_word, self.typing_options())
+            .with_fail_conditions(self.fail_conditions())
+            .with_overflow(self.overflow)
+            .with_error_display(self.errors)
+            .with_transforms(self.funbox.clone());
+
+        let typing_playground = match &self.layout {
+            Some(layout) => typing_playground.with_layout(layout.clone()),
+            None => typing_playground,
+        };
+        if self.keyboard {
+            typing_playground.with_keyboard()
+        } else {
+            typing_playground
+        }
+    }
 }
 
-fn main() -> io::Result<()> {
-    // setup terminal
+fn main() -> ExitCode {
     let args = Args::parse();
-    let mut terminal = tui::init()?;
+    let result = match &args.command {
+        Some(command) => run_command(&args, command),
+        None => run_interface(&args),
+    };
 
-    let dictionary = read_to_string(args.dictionary_dir).unwrap();
-    let dictionary = dictionary.lines().collect();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+/// Run the tests in the terminal. Everything they type is read beforehand, so a wrong option
+/// is reported before the terminal is touched, and the terminal is restored before any error
+/// happening during the tests is reported
+fn run_interface(args: &Args) -> Result<(), AppError> {
+    match &args.book {
+        Some(path) => {
+            let book = Book::load(path, args.chunk_words).map_err(AppError::config("--book"))?;
+            in_terminal(|terminal| run_book(terminal, &book, args))
+        }
+        None => {
+            let test = PreparedTest::new(args)?;
+            in_terminal(|terminal| run_test(terminal, test))
+        }
+    }
+}
+
+/// Set up the terminal for `run`, restoring it once `run` returns
+fn in_terminal(run: impl FnOnce(&mut tui::Tui) -> Result<(), AppError>) -> Result<(), AppError> {
+    let mut terminal = tui::init().map_err(AppError::Terminal)?;
+    let result = run(&mut terminal);
+    let restored = tui::restore().map_err(AppError::Terminal);
+    result.and(restored)
+}
+
+/// Test ready to be typed, with its text and replay already read
+struct PreparedTest {
+    /// Options of the test, the recorded ones when replaying
+    args: Args,
+    mode: &'static str,
+    source: String,
+    target_word: String,
+    typing_playground: TypingPlayground,
+    replay: Option<Replay>,
+    custom_text: Option<(CustomText, usize)>,
+    lesson: Option<(Layout, usize, usize)>,
+}
+
+impl PreparedTest {
+    /// Read and pick everything the test chosen by `args` types
+    fn new(args: &Args) -> Result<Self, AppError> {
+        let replay = match &args.replay {
+            Some(path) => Some((
+                path,
+                Replay::load(path).map_err(AppError::config("--replay"))?,
+            )),
+            None => None,
+        };
+        let args = match &replay {
+            Some((_, replay)) => args.replaying(replay)?,
+            None => args.clone(),
+        };
+
+        // Typing playground
+        let mut custom_text = None;
+        let mut lesson = None;
+        let (mode, source, target_word, typing_playground) = if let Some((path, replay)) = &replay {
+            let target_word = replay.target.clone();
+            let typing_playground = args.typing_playground(target_word.clone());
+            (
+                "replay",
+                path.display().to_string(),
+                target_word,
+                typing_playground,
+            )
+        } else if let Some(source) = &args.code {
+            let snippet = load_snippet(source).map_err(AppError::config("--code"))?;
+            let typing_playground = args
+                .typing_playground(snippet.code.clone())
+                .with_highlighting(snippet.language);
+            ("code", source.clone(), snippet.code, typing_playground)
+        } else if let Some(source) = &args.text {
+            if source == "-" && io::stdin().is_terminal() {
+                eprintln!("Type the text to practice, then press Ctrl+D");
+            }
+            let text =
+                CustomText::load(source, args.chunk_words).map_err(AppError::config("--text"))?;
+            let position = if args.shuffle {
+                rand::thread_rng().gen_range(0..text.chunks.len())
+            } else if args.resume {
+                text.saved_position()?
+            } else {
+                0
+            };
+
+            let target_word = text.chunks[position].clone();
+            custom_text = Some((text, position));
+            let typing_playground = args.typing_playground(target_word.clone());
+            ("text", source.clone(), target_word, typing_playground)
+        } else if let Some(ngrams) = &args.ngrams {
+            let ngrams = if is_auto(ngrams).map_err(AppError::config("--ngrams"))? {
+                slowest_ngrams()?
+            } else {
+                ngrams.clone()
+            };
+            let dictionary = args.dictionary()?;
 
-    let target_word = pick_random_words_from_dictionary(&dictionary, args.total_words).join(" ");
+            let words = pick_drill_words(
+                &ngrams,
+                &dictionary.words(),
+                args.drill_style,
+                args.total_words,
+            )
+            .map_err(AppError::config("--ngrams"))?;
 
-    // Typing playground
-    let mut typing_playground = TypingPlayground::new(target_word.clone());
+            let target_word = args.transformed(words.join(" "));
+            let typing_playground = args
+                .typing_playground(target_word.clone())
+                .with_direction(dictionary.manifest.direction);
+            ("drill", ngrams.join(","), target_word, typing_playground)
+        } else if let Some(number) = args.lesson {
+            let layout = args.keyboard_layout();
+            let passed = saved_progress(&layout)?;
+            let index = number.map_or(passed.min(LESSONS.len() - 1), |number| number as usize - 1);
+            let dictionary = args.dictionary()?;
 
-    typing_playground
-        .run(&mut terminal)
-        .expect("There was something wrong");
+            let target_word =
+                pick_lesson_words(index, &layout, &dictionary.words(), args.total_words).join(" ");
+            let typing_playground = args
+                .typing_playground(target_word.clone())
+                .with_direction(dictionary.manifest.direction);
+            lesson = Some((layout, index, passed));
+            (
+                "lesson",
+                LESSONS[index].title.to_string(),
+                target_word,
+                typing_playground,
+            )
+        } else if let Some(generator) = args.generate {
+            let dictionary = args.dictionary()?;
+
+            let target_word = args.transformed(generate_text(
+                generator,
+                &dictionary.words(),
+                args.total_words,
+            ));
+            let typing_playground = args.typing_playground(target_word.clone());
+            (
+                "generated",
+                generator.name().to_string(),
+                target_word,
+                typing_playground,
+            )
+        } else if args.zen {
+            let typing_playground = args.typing_playground(String::new());
+            ("zen", String::new(), String::new(), typing_playground)
+        } else {
+            let dictionary = args.dictionary()?;
+
+            let target_word = args.transformed(dictionary.pick_words(args.total_words).join(" "));
+            let typing_playground = args
+                .typing_playground(target_word.clone())
+                .with_direction(dictionary.manifest.direction);
+            (
+                "words",
+                args.dictionary_source(),
+                target_word,
+                typing_playground,
+            )
+        };
+
+        Ok(Self {
+            args,
+            mode,
+            source,
+            target_word,
+            typing_playground,
+            replay: replay.map(|(_, replay)| replay),
+            custom_text,
+            lesson,
+        })
+    }
+}
+
+/// Type a prepared test, then show its statistics
+fn run_test(terminal: &mut tui::Tui, test: PreparedTest) -> Result<(), AppError> {
+    let PreparedTest {
+        args,
+        mode,
+        source,
+        target_word,
+        mut typing_playground,
+        replay,
+        custom_text,
+        lesson,
+    } = test;
+    let replaying = replay.is_some();
+    // a replay starts playing its keys only once the terminal is ready
+    let mut input: Box<dyn InputSource> = match replay {
+        Some(replay) => Box::new(replay.play()),
+        None => Box::new(TerminalInput::default()),
+    };
+
+    match typing_playground.run(terminal, input.as_mut()) {
+        // a replay ends with its last key, the terminal never does
+        Err(error) if replaying && error.kind() == ErrorKind::UnexpectedEof => {}
+        Err(error) => return Err(AppError::Terminal(error)),
+        Ok(()) => {}
+    }
+    let user_events = typing_playground.get_user_events();
+    let fail_reason = typing_playground.get_fail_reason();
+    if let Some(path) = &args.record {
+        let replay = Replay::new(target_word.clone(), typing_playground.get_pressed_keys())
+            .with_options(args.typing_options())
+            .with_fail_conditions(args.fail_conditions())
+            .with_transforms(args.funbox.clone())
+            .with_error_display(args.errors)
+            .with_overflow(args.overflow);
+        let replay = match &args.layout {
+            Some(layout) => replay.with_layout(layout.name.clone()),
+            None => replay,
+        };
+        replay.save(path).map_err(AppError::config("--record"))?;
+    }
+    let (target_word, options) = if args.zen {
+        zen_target(&user_events, &args.typing_options())
+    } else {
+        (target_word, args.typing_options())
+    };
+    let summary =
+        RunSummary::new(&user_events, &target_word, &options).with_fail_reason(fail_reason);
+    let finger_analytics = FingerAnalytics::new(
+        &user_events,
+        &target_word,
+        &options,
+        &args.keyboard_layout(),
+    );
+
+    // Remember where the custom text stopped, moving on once the chunk is completed
+    if let Some((text, position)) = custom_text.filter(|_| !args.shuffle) {
+        if summary.completed {
+            text.save_position(position + 1)?;
+        } else {
+            text.save_position(position)?;
+        }
+    }
+    // replays were already counted when they were recorded
+    if !user_events.is_empty() && !replaying {
+        let entry = HistoryEntry::new(mode, &summary).with_fingers(&finger_analytics);
+        let entry = if source.is_empty() {
+            entry
+        } else {
+            entry.with_source(&source)
+        };
+        append_entry(&entry)?;
+        NgramTimings::from_events(&user_events, &target_word, &options).record()?;
+    }
 
     // Statistics view
-    StatisticsView::new(typing_playground.get_user_events(), target_word)
-        .run(&mut terminal)
-        .expect("Something went wrong with statistics");
+    let mut statistics_view = StatisticsView::new(user_events, target_word, options)
+        .with_fail_reason(fail_reason)
+        .with_finger_analytics(finger_analytics);
+    if args.zen {
+        statistics_view = statistics_view.with_zen();
+    }
+    if let Some((layout, index, passed)) = lesson {
+        // unlock the next lesson once the current one is passed
+        let lesson_result = LessonResult::new(index, &summary);
+        if lesson_result.passed && index == passed {
+            save_progress(&layout, passed + 1)?;
+        }
+        statistics_view = statistics_view.with_lesson_result(lesson_result);
+    }
+    statistics_view
+        .run(terminal, &mut TerminalInput::default())
+        .map_err(AppError::Terminal)
+}
 
-    tui::restore()?;
+/// Text and options to analyse a zen test with: the text typed stands for the target, so
+/// speed, rhythm and key statistics can be computed as for any other test
+fn zen_target(events: &[KeyEventSource], options: &TypingOptions) -> (String, TypingOptions) {
+    let typed = TypingEngine::from_events(events, "", options).input_sentence();
+    // the other options need a target, only the backspace restrictions applied
+    let options = TypingOptions {
+        confidence: options.confidence,
+        ..Default::default()
+    };
+    (typed, options)
+}
+
+/// Type a book test by test until the user exits, moving its bookmark forward every time a
+/// test is completed
+fn run_book(terminal: &mut tui::Tui, book: &Book, args: &Args) -> Result<(), AppError> {
+    let options = args.typing_options();
+    let mut bookmark = book.saved_bookmark()?;
+
+    loop {
+        let chapter = &book.chapters[bookmark.chapter];
+        let target_word = chapter.tests[bookmark.test].clone();
+
+        let mut typing_playground = args.typing_playground(target_word.clone());
+        typing_playground
+            .run(terminal, &mut TerminalInput::default())
+            .map_err(AppError::Terminal)?;
+        let user_events = typing_playground.get_user_events();
+        let fail_reason = typing_playground.get_fail_reason();
+        let summary =
+            RunSummary::new(&user_events, &target_word, &options).with_fail_reason(fail_reason);
+        let finger_analytics = FingerAnalytics::new(
+            &user_events,
+            &target_word,
+            &options,
+            &args.keyboard_layout(),
+        );
+
+        if !user_events.is_empty() {
+            append_entry(
+                &HistoryEntry::new("book", &summary)
+                    .with_source(&book.key)
+                    .with_chapter(&chapter.title)
+                    .with_fingers(&finger_analytics),
+            )?;
+            NgramTimings::from_events(&user_events, &target_word, &options).record()?;
+        }
+        let chapter_entries = read_entries()?;
+        let chapter_averages = averages(chapter_entries.iter().filter(|entry| {
+            entry.mode == "book"
+                && entry.source.as_ref() == Some(&book.key)
+                && entry.chapter.as_ref() == Some(&chapter.title)
+        }));
+        let book_progress = BookProgress {
+            chapter: chapter.title.clone(),
+            test: bookmark.test + 1,
+            total_tests: chapter.tests.len(),
+            chapter_averages,
+        };
+
+        // mistakes left in the text don't hold the book back, leaving or failing the test does
+        if summary.finished {
+            bookmark = book.next(&bookmark);
+            book.save_bookmark(&bookmark)?;
+        }
+
+        let mut statistics_view = StatisticsView::new(user_events, target_word, options.clone())
+            .with_fail_reason(fail_reason)
+            .with_book_progress(book_progress)
+            .with_finger_analytics(finger_analytics);
+        statistics_view
+            .run(terminal, &mut TerminalInput::default())
+            .map_err(AppError::Terminal)?;
+        if !statistics_view.next_requested() {
+            return Ok(());
+        }
+    }
+}
+
+/// Run a command that doesn't need the terminal interface
+fn run_command(args: &Args, command: &Command) -> Result<(), AppError> {
+    match command {
+        Command::Dictionary {
+            dictionary: DictionaryCommand::List,
+        } => {
+            print_dictionaries(&args.dictionary_dirs());
+            Ok(())
+        }
+        Command::Dictionary {
+            dictionary: DictionaryCommand::Check { file },
+        } => print_dictionary_issues(file),
+        Command::Dictionary {
+            dictionary: DictionaryCommand::Normalize { file, output },
+        } => {
+            let content = fs::read_to_string(file)
+                .map_err(AppError::dictionary(&file.display().to_string()))?;
+            let normalized = dictionary::normalize(&content);
+            match output {
+                Some(output) => fs::write(output, normalized).map_err(AppError::config("--output")),
+                None => {
+                    print!("{}", normalized);
+                    Ok(())
+                }
+            }
+        }
+        Command::Lessons => Ok(print_lessons(&args.keyboard_layout())?),
+        Command::Stats {
+            stats: StatsCommand::Bigrams { limit },
+        } => Ok(print_slowest_ngrams(2, *limit)?),
+        Command::Stats {
+            stats: StatsCommand::Trigrams { limit },
+        } => Ok(print_slowest_ngrams(3, *limit)?),
+    }
+}
+
+fn print_dictionaries(dirs: &[PathBuf]) {
+    for (path, dictionary) in list_dictionaries(dirs) {
+        let manifest = &dictionary.manifest;
+        println!(
+            "{:<6}{:<14}{:>7} words  {}{}{}",
+            manifest.language.as_deref().unwrap_or("-"),
+            manifest.name.as_deref().unwrap_or("-"),
+            dictionary.words.len(),
+            path.display(),
+            if manifest.direction == Direction::RightToLeft {
+                ", right to left"
+            } else {
+                ""
+            },
+            manifest
+                .author
+                .as_ref()
+                .map(|author| format!(", by {}", author))
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// Print the issues of a dictionary file, failing when there are any so scripts can tell
+fn print_dictionary_issues(file: &Path) -> Result<(), AppError> {
+    let source = file.display().to_string();
+    let content = fs::read_to_string(file).map_err(AppError::dictionary(&source))?;
+    let issues = dictionary::check(&content);
+    if issues.is_empty() {
+        println!("No issues found");
+        return Ok(());
+    }
+    for (line, issue) in &issues {
+        println!("{}:{}: {}", source, line, issue);
+    }
+    Err(AppError::DictionaryIssues {
+        source,
+        count: issues.len(),
+    })
+}
+
+fn print_lessons(layout: &Layout) -> io::Result<()> {
+    let passed = saved_progress(layout)?;
+    for (index, lesson) in LESSONS.iter().enumerate() {
+        let status = if index < passed { "✓" } else { " " };
+        println!(
+            "{} {:>2}. {:<40}{}",
+            status,
+            index + 1,
+            lesson.title,
+            lesson.new_chars(layout).iter().join(" ")
+        );
+    }
     Ok(())
 }
+
+fn print_slowest_ngrams(length: usize, limit: usize) -> io::Result<()> {
+    let timings = NgramTimings::load()?;
+    let slowest = timings.slowest(length, limit);
+    if slowest.is_empty() {
+        println!("Nothing typed yet");
+        return Ok(());
+    }
+
+    println!("{:<10}{:>12}{:>8}", "sequence", "average", "count");
+    for (sequence, timing) in slowest {
+        println!(
+            "{:<10}{:>10}ms{:>8}",
+            sequence,
+            timing.average().as_millis(),
+            timing.count
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod print_dictionary_issues_tests {
+    use std::{env, fs, process};
+
+    use crate::error::AppError;
+
+    use super::print_dictionary_issues;
+
+    #[test]
+    fn clean_dictionaries_pass() {
+        let file = env::temp_dir().join(format!("monclitype-clean-{}.txt", process::id()));
+        fs::write(&file, "# language: en\nthe\nof\n").unwrap();
+
+        let result = print_dictionary_issues(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn dictionaries_with_issues_fail() {
+        let file = env::temp_dir().join(format!("monclitype-issues-{}.txt", process::id()));
+        fs::write(&file, "# language: en\nthe\n\nthe\n").unwrap();
+
+        let result = print_dictionary_issues(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(AppError::DictionaryIssues { count: 2, .. })
+        ));
+        assert_eq!(result.unwrap_err().exit_code(), 7);
+    }
+}
+
+#[cfg(test)]
+mod args_tests {
+    use std::time::Duration;
+
+    use clap::Parser;
+
+    use crate::{
+        failure::FailConditions,
+        input::Replay,
+        transforms::Transform,
+        views::typing_playground::{ErrorDisplay, Overflow},
+    };
+
+    use super::Args;
+
+    #[test]
+    fn funbox_only_twists_generated_texts() {
+        for mode in [
+            ["--code", "rust"],
+            ["--text", "text.txt"],
+            ["--book", "book.txt"],
+            ["--lesson", "1"],
+        ] {
+            let result =
+                Args::try_parse_from(["monclitype", "--funbox", "mirror", mode[0], mode[1]]);
+
+            assert!(result.is_err(), "--funbox is taken with {}", mode[0]);
+        }
+        assert!(Args::try_parse_from(["monclitype", "--funbox", "mirror", "--zen"]).is_err());
+        assert!(
+            Args::try_parse_from(["monclitype", "--funbox", "mirror", "--ngrams", "th"]).is_ok()
+        );
+    }
+
+    #[test]
+    fn replays_restore_how_they_were_recorded() {
+        let fail_conditions = FailConditions {
+            sudden_death: false,
+            min_accuracy: Some(90.0),
+            min_wpm: Some(40.0),
+            grace_period: Duration::from_secs(2),
+        };
+        let replay = Replay::new("a".to_string(), vec![])
+            .with_fail_conditions(fail_conditions.clone())
+            .with_transforms(vec![Transform::Memory])
+            .with_error_display(ErrorDisplay::Hidden)
+            .with_overflow(Overflow::Compact);
+        let args = Args::try_parse_from(["monclitype", "--replay", "test.replay"]).unwrap();
+
+        let result = args.replaying(&replay).unwrap();
+
+        assert_eq!(result.fail_conditions(), fail_conditions);
+        assert_eq!(result.funbox, vec![Transform::Memory]);
+        assert_eq!(result.errors, ErrorDisplay::Hidden);
+        assert_eq!(result.overflow, Overflow::Compact);
+    }
+}
@@ -0,0 +1,422 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::Itertools;
+
+use crate::{
+    engine::TypingEngine,
+    failure::FailReason,
+    sentences::verify_sentence_input,
+    splitter::is_end_reached,
+    types::{KeyEventSource, TypingOptions},
+};
+
+/// Average length of a word, used to convert characters to words
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// Number of words reported as the slowest of a test
+const SLOWEST_WORDS: usize = 3;
+
+/// Outcome of a test
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RunSummary {
+    /// Words per minute, counting only the characters matching the target sentence
+    pub wpm: f64,
+    /// Percentage of keystrokes that matched the expected character
+    pub accuracy: f64,
+    pub duration: Duration,
+    /// Whether the input matches the target sentence and no fail condition was met
+    pub completed: bool,
+    /// Whether the end of the target sentence was reached, mistakes or not, and no fail
+    /// condition was met
+    pub finished: bool,
+    pub fail_reason: Option<FailReason>,
+    /// How steady the raw speed was from one second to the next, in percentage
+    pub consistency: f64,
+    /// Highest speed reached on a single word, in words per minute
+    pub burst_wpm: f64,
+    /// Words typed the slowest, slowest first
+    pub slowest_words: Vec<WordSpeed>,
+}
+
+/// Speed at which a word of the target sentence was typed
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordSpeed {
+    pub word: String,
+    pub wpm: f64,
+}
+
+/// When a word of the target sentence was typed
+///
+/// A word starts with the key that ended the previous word, so the time taken to reach the
+/// word counts towards it, and ends with its last key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordTiming {
+    pub start: Instant,
+    pub end: Instant,
+}
+
+/// How a word of the target sentence was typed
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordBreakdown {
+    /// Index of the word in the target sentence
+    pub position: usize,
+    pub target: String,
+    pub typed: String,
+    pub duration: Duration,
+    pub wpm: Option<f64>,
+    /// Whether the word and its separator match the target
+    pub correct: bool,
+}
+
+impl WordTiming {
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start)
+    }
+}
+
+impl RunSummary {
+    pub fn new(events: &[KeyEventSource], target_sentence: &str, options: &TypingOptions) -> Self {
+        let engine = TypingEngine::from_events(events, target_sentence, options);
+        let duration = elapsed(events);
+        let word_speeds = word_speeds(events, target_sentence, options);
+
+        Self {
+            wpm: words_per_minute(engine.correct_chars(), duration),
+            accuracy: engine.accuracy(),
+            duration,
+            completed: verify_sentence_input(&engine.input_sentence(), target_sentence),
+            finished: is_end_reached(events, target_sentence, options),
+            fail_reason: None,
+            consistency: consistency(events),
+            burst_wpm: word_speeds
+                .iter()
+                .map(|speed| speed.wpm)
+                .fold(0.0, f64::max),
+            slowest_words: word_speeds
+                .into_iter()
+                .sorted_by(|a, b| a.wpm.total_cmp(&b.wpm))
+                .take(SLOWEST_WORDS)
+                .collect(),
+        }
+    }
+
+    /// Mark the test as failed when a fail condition was met
+    pub fn with_fail_reason(mut self, fail_reason: Option<FailReason>) -> Self {
+        self.completed &= fail_reason.is_none();
+        self.finished &= fail_reason.is_none();
+        self.fail_reason = fail_reason;
+        self
+    }
+}
+
+/// Time between the first and the last event
+pub fn elapsed(events: &[KeyEventSource]) -> Duration {
+    match (events.first(), events.last()) {
+        (Some(first), Some(last)) => last.timestamp.duration_since(first.timestamp),
+        _ => Duration::ZERO,
+    }
+}
+
+pub fn words_per_minute(chars: usize, duration: Duration) -> f64 {
+    if duration.is_zero() {
+        return 0.0;
+    }
+    chars as f64 / CHARS_PER_WORD / (duration.as_secs_f64() / 60.0)
+}
+
+/// Whether the event types a character, as opposed to deleting or being ignored
+fn is_typed_char(event: &KeyEventSource) -> bool {
+    match event.key {
+        KeyCode::Char(_) => event.modifiers != KeyModifiers::CONTROL,
+        KeyCode::Enter | KeyCode::Tab => true,
+        _ => false,
+    }
+}
+
+/// Raw speed of every whole second of the test, counting every typed character
+pub fn raw_wpm_per_second(events: &[KeyEventSource]) -> Vec<f64> {
+    let Some(first) = events.first() else {
+        return vec![];
+    };
+    let seconds = elapsed(events).as_secs() as usize;
+
+    let mut chars_per_second = vec![0; seconds];
+    for event in events.iter().filter(|event| is_typed_char(event)) {
+        let second = event.timestamp.duration_since(first.timestamp).as_secs() as usize;
+        if let Some(chars) = chars_per_second.get_mut(second) {
+            *chars += 1;
+        }
+    }
+
+    chars_per_second
+        .into_iter()
+        .map(|chars| words_per_minute(chars, Duration::from_secs(1)))
+        .collect()
+}
+
+/// 100% minus the coefficient of variation of the raw speed of every second, so a test
+/// typed at a constant speed is 100% consistent. Tests shorter than two seconds are
+/// considered consistent
+pub fn consistency(events: &[KeyEventSource]) -> f64 {
+    let samples = raw_wpm_per_second(events);
+    if samples.len() < 2 {
+        return 100.0;
+    }
+
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance =
+        samples.iter().map(|wpm| (wpm - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+    (100.0 - variance.sqrt() / mean * 100.0).max(0.0)
+}
+
+/// Timing of every word of the target sentence, or `None` for the words never reached
+pub fn word_timings(
+    events: &[KeyEventSource],
+    target_sentence: &str,
+    options: &TypingOptions,
+) -> Vec<Option<WordTiming>> {
+    let mut engine = TypingEngine::new(target_sentence, options.clone());
+    let mut timings = vec![None; engine.target_words().len()];
+    let mut previous_timestamp = None;
+
+    for event in events {
+        engine.handle_event(event);
+
+        // a key completing a separator leaves an empty word behind, but belongs to the
+        // word it completes
+        let current_word = engine.input_words().last().unwrap();
+        let is_empty = current_word.text.is_empty() && current_word.separator.is_empty();
+        let index =
+            engine.current_word_index() - usize::from(is_empty && engine.current_word_index() > 0);
+
+        if let Some(timing) = timings.get_mut(index) {
+            let start = previous_timestamp.unwrap_or(event.timestamp);
+            let timing = timing.get_or_insert(WordTiming {
+                start,
+                end: event.timestamp,
+            });
+            timing.end = event.timestamp;
+        }
+        previous_timestamp = Some(event.timestamp);
+    }
+
+    timings
+}
+
+/// How every word reached by the user was typed, in the order of the target sentence
+pub fn word_breakdown(
+    events: &[KeyEventSource],
+    target_sentence: &str,
+    options: &TypingOptions,
+) -> Vec<WordBreakdown> {
+    let engine = TypingEngine::from_events(events, target_sentence, options);
+
+    word_timings(events, target_sentence, options)
+        .into_iter()
+        .zip(engine.target_words())
+        .zip(engine.input_words())
+        .enumerate()
+        .filter_map(|(position, ((timing, target_word), input_word))| {
+            let duration = timing?.duration();
+            let chars = target_word.text.chars().count() + target_word.separator.chars().count();
+            Some(WordBreakdown {
+                position,
+                target: target_word.text.to_string(),
+                typed: input_word.text.clone(),
+                duration,
+                // a word typed in no time has no meaningful speed
+                wpm: (!duration.is_zero()).then(|| words_per_minute(chars, duration)),
+                correct: input_word.text == target_word.text
+                    && input_word.separator == target_word.separator,
+            })
+        })
+        .collect()
+}
+
+/// Speed of every word reached, words typed in no time excluded
+fn word_speeds(
+    events: &[KeyEventSource],
+    target_sentence: &str,
+    options: &TypingOptions,
+) -> Vec<WordSpeed> {
+    word_breakdown(events, target_sentence, options)
+        .into_iter()
+        .filter_map(|breakdown| {
+            Some(WordSpeed {
+                wpm: breakdown.wpm?,
+                word: breakdown.target,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod run_summary_tests {
+    use std::time::Duration;
+
+    use crate::{failure::FailReason, types::TypingOptions, views::test_harness::ScriptedEvents};
+
+    use super::{
+        consistency, word_breakdown, word_timings, words_per_minute, RunSummary, WordBreakdown,
+        WordSpeed,
+    };
+
+    #[test]
+    fn completed_run() {
+        let events = ScriptedEvents::new()
+            .type_at("ab cd", &[0, 1000, 2000, 3000, 4000])
+            .events();
+
+        let result = RunSummary::new(&events, "ab cd", &TypingOptions::default());
+
+        assert_eq!(result.duration, Duration::from_secs(4));
+        assert_eq!(result.wpm, 15.0);
+        assert_eq!(result.accuracy, 100.0);
+        assert!(result.completed);
+    }
+
+    #[test]
+    fn run_finished_with_a_typo() {
+        let events = ScriptedEvents::new()
+            .type_at("ab cx", &[0, 100, 200, 300, 400])
+            .events();
+
+        let result = RunSummary::new(&events, "ab cd", &TypingOptions::default());
+
+        assert!(!result.completed);
+        assert!(result.finished);
+    }
+
+    #[test]
+    fn run_left_or_failed_before_the_end_is_not_finished() {
+        let events = ScriptedEvents::new()
+            .type_at("ab c", &[0, 100, 200, 300])
+            .events();
+
+        let left = RunSummary::new(&events, "ab cd", &TypingOptions::default());
+        let failed = RunSummary::new(&events, "ab c", &TypingOptions::default())
+            .with_fail_reason(Some(FailReason::Mistake));
+
+        assert!(!left.finished);
+        assert!(!failed.finished);
+    }
+
+    #[test]
+    fn run_without_events() {
+        let result = RunSummary::new(&[], "ab", &TypingOptions::default());
+
+        assert_eq!(result.wpm, 0.0);
+        assert!(!result.completed);
+    }
+
+    #[test]
+    fn burst_and_slowest_words() {
+        let events = ScriptedEvents::new()
+            .type_at("ab cd", &[0, 1000, 1200, 1500, 1800])
+            .events();
+
+        let result = RunSummary::new(&events, "ab cd", &TypingOptions::default());
+
+        // "ab " is typed in 1.2s, "cd" in 0.6s counting the key that ended "ab "
+        assert_eq!(result.burst_wpm, 40.0);
+        assert_eq!(
+            result.slowest_words,
+            vec![
+                WordSpeed {
+                    word: "ab".to_string(),
+                    wpm: 30.0
+                },
+                WordSpeed {
+                    word: "cd".to_string(),
+                    wpm: 40.0
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn word_timings_of_unfinished_run() {
+        let events = ScriptedEvents::new()
+            .type_at("ab c", &[0, 1000, 1200, 1500])
+            .events();
+
+        let result = word_timings(&events, "ab cd ef", &TypingOptions::default());
+
+        assert_eq!(
+            result[0].map(|timing| timing.duration()),
+            Some(Duration::from_millis(1200))
+        );
+        assert_eq!(
+            result[1].map(|timing| timing.duration()),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(result[2], None);
+    }
+
+    #[test]
+    fn breakdown_of_words_reached() {
+        let events = ScriptedEvents::new()
+            .type_at("ax cd", &[0, 1000, 1200, 1500, 1800])
+            .events();
+
+        let result = word_breakdown(&events, "ab cd ef", &TypingOptions::default());
+
+        assert_eq!(
+            result,
+            vec![
+                WordBreakdown {
+                    position: 0,
+                    target: "ab".to_string(),
+                    typed: "ax".to_string(),
+                    duration: Duration::from_millis(1200),
+                    wpm: Some(30.0),
+                    correct: false,
+                },
+                WordBreakdown {
+                    position: 1,
+                    target: "cd".to_string(),
+                    typed: "cd".to_string(),
+                    duration: Duration::from_millis(600),
+                    wpm: Some(60.0),
+                    // the separator wasn't typed
+                    correct: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_speed_is_consistent() {
+        let events = ScriptedEvents::new()
+            .type_at("abcdefgh", &[0, 500, 1000, 1500, 2000, 2500, 3000, 3500])
+            .events();
+
+        let result = consistency(&events);
+
+        assert_eq!(result, 100.0);
+    }
+
+    #[test]
+    fn variable_speed_is_less_consistent() {
+        let events = ScriptedEvents::new()
+            .type_at("abcdefghi", &[0, 100, 200, 300, 400, 500, 1400, 1900, 2400])
+            .events();
+
+        let result = consistency(&events);
+
+        // 6 then 2 keys in the whole seconds: the standard deviation is half the mean
+        assert_eq!(result, 50.0);
+    }
+
+    #[test]
+    fn words_per_minute_of_a_minute() {
+        let result = words_per_minute(50, Duration::from_secs(60));
+
+        assert_eq!(result, 10.0);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::BTreeMap, io, time::Duration};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::Itertools;
+
+use crate::{
+    engine::TypingEngine,
+    storage::{read_key_values, write_key_values},
+    types::{KeyEventSource, TypingOptions},
+};
+
+const NGRAMS_FILE: &str = "ngrams.tsv";
+
+/// Lengths of the character sequences that are timed
+const NGRAM_LENGTHS: [usize; 2] = [2, 3];
+
+/// Time spent typing a character sequence, added up over every time it was typed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NgramTiming {
+    pub total: Duration,
+    pub count: usize,
+}
+
+impl NgramTiming {
+    pub fn average(&self) -> Duration {
+        self.total / self.count.max(1) as u32
+    }
+}
+
+/// Timings of the bigrams and trigrams typed, by sequence
+///
+/// A sequence is timed from its first to its last key, only when every key was right and
+/// no whitespace is in between, so the timings reflect the flow from one finger to the next
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NgramTimings(BTreeMap<String, NgramTiming>);
+
+impl NgramTimings {
+    pub fn from_events(
+        events: &[KeyEventSource],
+        target_sentence: &str,
+        options: &TypingOptions,
+    ) -> Self {
+        let mut timings = Self::default();
+        let mut engine = TypingEngine::new(target_sentence, options.clone());
+        let mut streak = vec![];
+
+        for event in events {
+            let expected_char = engine.expected_char();
+            engine.handle_event(event);
+
+            match event.key {
+                KeyCode::Char(c)
+                    if event.modifiers != KeyModifiers::CONTROL
+                        && expected_char == Some(c)
+                        && !c.is_whitespace() =>
+                {
+                    streak.push((c, event.timestamp));
+                }
+                _ => {
+                    streak.clear();
+                    continue;
+                }
+            }
+
+            for length in NGRAM_LENGTHS.into_iter().filter(|&n| streak.len() >= n) {
+                let ngram = &streak[streak.len() - length..];
+                let sequence = ngram.iter().map(|(c, _)| c).collect::<String>();
+                let duration = event.timestamp.duration_since(ngram[0].1);
+                timings.add(sequence, duration, 1);
+            }
+        }
+
+        timings
+    }
+
+    fn add(&mut self, sequence: String, total: Duration, count: usize) {
+        let timing = self.0.entry(sequence).or_default();
+        timing.total += total;
+        timing.count += count;
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (sequence, timing) in &other.0 {
+            self.add(sequence.clone(), timing.total, timing.count);
+        }
+    }
+
+    /// Sequences of `length` characters with the highest average time, slowest first
+    pub fn slowest(&self, length: usize, limit: usize) -> Vec<(&str, NgramTiming)> {
+        self.0
+            .iter()
+            .filter(|(sequence, _)| sequence.chars().count() == length)
+            .sorted_by(|(_, a), (_, b)| b.average().cmp(&a.average()))
+            .take(limit)
+            .map(|(sequence, timing)| (sequence.as_str(), *timing))
+            .collect()
+    }
+
+    /// Timings of every test in the history
+    pub fn load() -> io::Result<Self> {
+        let values = read_key_values(NGRAMS_FILE)?;
+
+        Ok(Self(
+            values
+                .into_iter()
+                .filter_map(|(sequence, value)| {
+                    let (total, count) = value.split_once(' ')?;
+                    let timing = NgramTiming {
+                        total: Duration::from_micros(total.parse().ok()?),
+                        count: count.parse().ok()?,
+                    };
+                    Some((sequence, timing))
+                })
+                .collect(),
+        ))
+    }
+
+    /// Add the timings to the ones of the history
+    pub fn record(&self) -> io::Result<()> {
+        let mut timings = Self::load()?;
+        timings.merge(self);
+
+        let values = timings
+            .0
+            .iter()
+            .map(|(sequence, timing)| {
+                let value = format!("{} {}", timing.total.as_micros(), timing.count);
+                (sequence.clone(), value)
+            })
+            .collect();
+        write_key_values(NGRAMS_FILE, &values)
+    }
+}
+
+#[cfg(test)]
+mod ngram_timings_tests {
+    use std::time::Duration;
+
+    use crossterm::event::KeyCode;
+
+    use crate::{types::TypingOptions, views::test_harness::ScriptedEvents};
+
+    use super::{NgramTiming, NgramTimings};
+
+    fn timing(millis: u64, count: usize) -> NgramTiming {
+        NgramTiming {
+            total: Duration::from_millis(millis),
+            count,
+        }
+    }
+
+    #[test]
+    fn bigrams_and_trigrams_are_timed() {
+        let events = ScriptedEvents::new()
+            .press_at(KeyCode::Char('t'), 0)
+            .press_at(KeyCode::Char('h'), 100)
+            .press_at(KeyCode::Char('e'), 300)
+            .press_at(KeyCode::Char(' '), 400)
+            .press_at(KeyCode::Char('t'), 500)
+            .press_at(KeyCode::Char('h'), 700)
+            .events();
+
+        let result = NgramTimings::from_events(&events, "the th", &TypingOptions::default());
+
+        assert_eq!(
+            result.slowest(2, 5),
+            vec![("he", timing(200, 1)), ("th", timing(300, 2))]
+        );
+        assert_eq!(result.slowest(3, 5), vec![("the", timing(300, 1))]);
+    }
+
+    #[test]
+    fn mistakes_break_sequences() {
+        let events = ScriptedEvents::new()
+            .press_at(KeyCode::Char('a'), 0)
+            .press_at(KeyCode::Char('x'), 100)
+            .press_at(KeyCode::Backspace, 200)
+            .press_at(KeyCode::Char('b'), 300)
+            .press_at(KeyCode::Char('c'), 400)
+            .events();
+
+        let result = NgramTimings::from_events(&events, "abc", &TypingOptions::default());
+
+        assert_eq!(result.slowest(2, 5), vec![("bc", timing(100, 1))]);
+        assert!(result.slowest(3, 5).is_empty());
+    }
+
+    #[test]
+    fn timings_are_merged() {
+        let mut timings = NgramTimings::default();
+        timings.add("ab".to_string(), Duration::from_millis(100), 1);
+        let mut other = NgramTimings::default();
+        other.add("ab".to_string(), Duration::from_millis(300), 1);
+
+        timings.merge(&other);
+
+        assert_eq!(timings.slowest(2, 5), vec![("ab", timing(400, 2))]);
+        assert_eq!(timing(400, 2).average(), Duration::from_millis(200));
+    }
+}
@@ -1,40 +1,74 @@
-use std::iter::zip;
-
-use itertools::Itertools;
+use itertools::{EitherOrBoth, Itertools};
 
 use crate::{
-    sentences::build_sentence,
-    types::{GameFinished, KeyEventSource, WordGameStatus, WordMatch},
+    engine::TypingEngine,
+    types::{GameFinished, KeyEventSource, TypingOptions, WordGameStatus, WordMatch},
 };
 
+/// A word of the target sentence along with the whitespace that follows it
+#[derive(Debug, PartialEq)]
+pub struct TargetWord<'a> {
+    pub text: &'a str,
+    pub separator: &'a str,
+}
+
+/// A word typed by the user, split the same way as [TargetWord]
+#[derive(Debug, Default, PartialEq)]
+pub struct InputWord {
+    pub text: String,
+    pub separator: String,
+}
+
 pub fn get_current_game_status(
-    events: &Vec<KeyEventSource>,
+    events: &[KeyEventSource],
     target_sentence: &str,
+    options: &TypingOptions,
 ) -> Result<WordGameStatus, GameFinished> {
-    let input_sentence = build_sentence(&events);
-    let input_words = input_sentence.split(" ").collect_vec();
-    let input_words_length = input_words.len();
+    let engine = TypingEngine::from_events(events, target_sentence, options);
+    let target_words = engine.target_words();
+    let input_words = engine.input_words();
+    let current_word_index = engine.current_word_index();
 
-    let target_words = target_sentence.split(" ").collect_vec();
-    let target_words_length = target_words.len();
+    // without a target, whatever is typed is the text
+    if options.zen {
+        let typed_word = |word: &InputWord| {
+            format!("{}{}", word.text, word.separator)
+                .chars()
+                .map(|c| EitherOrBoth::Both(c, c))
+                .collect()
+        };
+        return Ok(WordGameStatus(
+            input_words[..current_word_index]
+                .iter()
+                .map(typed_word)
+                .collect(),
+            typed_word(&input_words[current_word_index]),
+            None,
+        ));
+    }
 
-    if input_words_length > target_words_length {
+    if current_word_index >= target_words.len() {
         return Err(GameFinished);
     }
 
-    let mut already_written_words = zip(
-        input_words[..input_words_length].into_iter(),
-        target_words[..input_words_length].into_iter(),
-    )
-    .map(|(&a, &b)| zip_input_target_word(a, b))
-    .collect_vec();
-    let current_written_word = already_written_words.pop().unwrap();
-    let not_written_sentence = target_words[input_words_length..].into_iter().join(" ");
+    let already_written_words = input_words[..current_word_index]
+        .iter()
+        .zip(target_words.iter())
+        .map(|(input, target)| zip_input_target_words(input, target))
+        .collect_vec();
+    let current_written_word = zip_input_target_words(
+        &input_words[current_word_index],
+        &target_words[current_word_index],
+    );
+    let not_written_sentence = target_words[current_word_index + 1..]
+        .iter()
+        .map(|word| format!("{}{}", word.text, word.separator))
+        .join("");
 
     Ok(WordGameStatus(
         already_written_words,
         current_written_word,
-        if not_written_sentence.len() == 0 {
+        if not_written_sentence.is_empty() {
             None
         } else {
             Some(not_written_sentence)
@@ -42,6 +76,63 @@ pub fn get_current_game_status(
     ))
 }
 
+/// Whether the end of the target sentence was reached, whether the text typed matches it or
+/// not. A text typed freely has no end
+pub fn is_end_reached(
+    events: &[KeyEventSource],
+    target_sentence: &str,
+    options: &TypingOptions,
+) -> bool {
+    if options.zen {
+        return false;
+    }
+
+    // check if user has typed last word
+    let Ok(WordGameStatus(_, current_word, future_words)) =
+        get_current_game_status(events, target_sentence, options)
+    else {
+        return true;
+    };
+    if future_words.is_some_and(|word| !word.is_empty()) {
+        return false;
+    }
+
+    // there is no remaining letters in target word to fill
+    current_word.iter().all(|c| c.is_both())
+}
+
+/// Split the target sentence in words. Each word keeps the whitespace that follows it,
+/// so the sentence can be rebuilt by concatenating them
+pub fn split_target_words(target_sentence: &str) -> Vec<TargetWord<'_>> {
+    let mut words = vec![];
+    let mut rest = target_sentence;
+
+    while !rest.is_empty() {
+        let text_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let separator_end = rest[text_end..]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(rest.len(), |index| text_end + index);
+        words.push(TargetWord {
+            text: &rest[..text_end],
+            separator: &rest[text_end..separator_end],
+        });
+        rest = &rest[separator_end..];
+    }
+
+    words
+}
+
+/// Zip both words, including their separators
+pub fn zip_input_target_words(input_word: &InputWord, target_word: &TargetWord) -> WordMatch {
+    zip_input_target_word(&input_word.text, target_word.text)
+        .into_iter()
+        .chain(zip_input_target_word(
+            &input_word.separator,
+            target_word.separator,
+        ))
+        .collect()
+}
+
 pub fn zip_input_target_word(input_sentence: &str, target_sentence: &str) -> WordMatch {
     input_sentence
         .chars()
@@ -51,12 +142,12 @@ pub fn zip_input_target_word(input_sentence: &str, target_sentence: &str) -> Wor
 
 #[cfg(test)]
 mod current_game_status_tests {
-    use std::time::Instant;
-
-    use crossterm::event::KeyCode;
     use itertools::EitherOrBoth;
 
-    use crate::types::{KeyEventSource, WordGameStatus};
+    use crate::{
+        types::{TypingOptions, WordGameStatus},
+        views::test_harness::ScriptedEvents,
+    };
 
     use super::get_current_game_status;
 
@@ -70,12 +161,12 @@ mod current_game_status_tests {
                 EitherOrBoth::Right('l'),
                 EitherOrBoth::Right('l'),
                 EitherOrBoth::Right('o'),
-            ]
-            .into(),
+                EitherOrBoth::Right(' '),
+            ],
             "world".to_string().into(),
         );
 
-        let result = get_current_game_status(&vec![], "hello world");
+        let result = get_current_game_status(&[], "hello world", &TypingOptions::default());
 
         assert_eq!(result.unwrap(), expected);
     }
@@ -88,6 +179,7 @@ mod current_game_status_tests {
             EitherOrBoth::Both('l', 'l'),
             EitherOrBoth::Both('l', 'l'),
             EitherOrBoth::Both('o', 'o'),
+            EitherOrBoth::Both(' ', ' '),
         ];
         let word_2 = vec![
             EitherOrBoth::Both('w', 'w'),
@@ -96,56 +188,10 @@ mod current_game_status_tests {
             EitherOrBoth::Both('l', 'l'),
             EitherOrBoth::Both('d', 'd'),
         ];
-        let timestamp = Instant::now();
-        let events = vec![
-            KeyEventSource {
-                key: KeyCode::Char('h'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('e'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('l'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('l'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('o'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char(' '),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('w'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('o'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('r'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('l'),
-                timestamp,
-            },
-            KeyEventSource {
-                key: KeyCode::Char('d'),
-                timestamp,
-            },
-        ];
+        let events = ScriptedEvents::new().type_text("hello world").events();
         let expected = WordGameStatus(vec![word_1], word_2, None);
 
-        let result = get_current_game_status(&events, "hello world");
+        let result = get_current_game_status(&events, "hello world", &TypingOptions::default());
 
         assert_eq!(result.unwrap(), expected);
     }
@@ -153,15 +199,91 @@ mod current_game_status_tests {
     #[test]
     fn game_finished() {
         let result = get_current_game_status(
-            &vec![KeyEventSource {
-                key: KeyCode::Char(' '),
-                timestamp: Instant::now(),
-            }],
+            &ScriptedEvents::new().type_text(" ").events(),
             "",
+            &TypingOptions::default(),
         );
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn zen_game_never_finishes() {
+        let events = ScriptedEvents::new().type_text("a b").events();
+        let options = TypingOptions {
+            zen: true,
+            ..Default::default()
+        };
+
+        let result = get_current_game_status(&events, "", &options);
+
+        assert_eq!(
+            result.unwrap(),
+            WordGameStatus(
+                vec![vec![
+                    EitherOrBoth::Both('a', 'a'),
+                    EitherOrBoth::Both(' ', ' ')
+                ]],
+                vec![EitherOrBoth::Both('b', 'b')],
+                None
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod split_target_words_tests {
+    use super::{split_target_words, TargetWord};
+
+    #[test]
+    fn words_keep_their_separators() {
+        let result = split_target_words("fn a() {\n    b\n}");
+
+        assert_eq!(
+            result,
+            vec![
+                TargetWord {
+                    text: "fn",
+                    separator: " "
+                },
+                TargetWord {
+                    text: "a()",
+                    separator: " "
+                },
+                TargetWord {
+                    text: "{",
+                    separator: "\n    "
+                },
+                TargetWord {
+                    text: "b",
+                    separator: "\n"
+                },
+                TargetWord {
+                    text: "}",
+                    separator: ""
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn leading_whitespace_is_an_empty_word() {
+        let result = split_target_words("  a");
+
+        assert_eq!(
+            result,
+            vec![
+                TargetWord {
+                    text: "",
+                    separator: "  "
+                },
+                TargetWord {
+                    text: "a",
+                    separator: ""
+                },
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
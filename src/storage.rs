@@ -0,0 +1,73 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, read_to_string, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::PathBuf,
+};
+
+use itertools::Itertools;
+
+/// Directory where MoncliType keeps data between runs
+///
+/// It's `$MONCLITYPE_DATA_DIR` when set, otherwise `monclitype` inside `$XDG_DATA_HOME` or
+/// `~/.local/share`
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("MONCLITYPE_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_default()
+        .join("monclitype")
+}
+
+/// Read a file of `key<TAB>value` lines from the data directory. A missing file is empty
+pub fn read_key_values(name: &str) -> io::Result<BTreeMap<String, String>> {
+    let content = match read_to_string(data_dir().join(name)) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(error) => return Err(error),
+    };
+
+    Ok(content
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+/// Write a file of `key<TAB>value` lines to the data directory, replacing its content
+pub fn write_key_values(name: &str, values: &BTreeMap<String, String>) -> io::Result<()> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+
+    let content = values
+        .iter()
+        .map(|(key, value)| format!("{}\t{}\n", key, value))
+        .join("");
+    fs::write(dir.join(name), content)
+}
+
+/// Read every line of a file in the data directory. A missing file has no lines
+pub fn read_lines(name: &str) -> io::Result<Vec<String>> {
+    match read_to_string(data_dir().join(name)) {
+        Ok(content) => Ok(content.lines().map(str::to_string).collect()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(error) => Err(error),
+    }
+}
+
+/// Append a line to a file in the data directory, creating it when needed
+pub fn append_line(name: &str, line: &str) -> io::Result<()> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(name))?;
+    writeln!(file, "{}", line)
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::splitter::split_target_words;
+
+/// Time the text of a memory test stays visible for every word, before it's hidden
+const MEMORY_PREVIEW_PER_WORD: Duration = Duration::from_millis(400);
+
+/// Change made to the text of a test, or to the way it's shown
+///
+/// Transforms are applied in the order they are given, so they can be combined
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Transform {
+    /// Type the words in reverse order
+    Reverse,
+    /// Capitalize letters at random
+    Caps,
+    /// Replace letters with the digits that look like them
+    Leet,
+    /// Spell every word backwards
+    Mirror,
+    /// Show the text for a few seconds, then type it from memory
+    Memory,
+    /// Hide the word being typed, so you have to read ahead
+    ReadAhead,
+}
+
+impl Transform {
+    /// Transformed text. The whitespace between words is kept as it is, and the transforms
+    /// hiding the text leave it unchanged
+    pub fn apply(&self, text: &str) -> String {
+        let words = split_target_words(text);
+        match self {
+            Self::Reverse => words
+                .iter()
+                .map(|word| word.text)
+                .rev()
+                .zip(words.iter().map(|word| word.separator))
+                .map(|(text, separator)| format!("{}{}", text, separator))
+                .join(""),
+            Self::Caps => {
+                let mut rng = rand::thread_rng();
+                text.chars()
+                    .map(|c| {
+                        if rng.gen_bool(0.5) {
+                            c.to_uppercase().collect::<String>()
+                        } else {
+                            c.to_string()
+                        }
+                    })
+                    .join("")
+            }
+            Self::Leet => text.chars().map(leet).collect(),
+            Self::Mirror => words
+                .iter()
+                .map(|word| {
+                    format!(
+                        "{}{}",
+                        word.text.chars().rev().collect::<String>(),
+                        word.separator
+                    )
+                })
+                .join(""),
+            Self::Memory | Self::ReadAhead => text.to_string(),
+        }
+    }
+}
+
+/// Apply every transform to `text`, in order
+pub fn apply_transforms(text: &str, transforms: &[Transform]) -> String {
+    transforms
+        .iter()
+        .fold(text.to_string(), |text, transform| transform.apply(&text))
+}
+
+/// Time the text of a memory test stays visible
+pub fn memory_preview(text: &str) -> Duration {
+    MEMORY_PREVIEW_PER_WORD * split_target_words(text).len() as u32
+}
+
+fn leet(c: char) -> char {
+    match c.to_ascii_lowercase() {
+        'a' => '4',
+        'e' => '3',
+        'i' => '1',
+        'o' => '0',
+        's' => '5',
+        't' => '7',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod transforms_tests {
+    use std::time::Duration;
+
+    use super::{apply_transforms, memory_preview, Transform};
+
+    #[test]
+    fn reversed_words_keep_the_separators() {
+        let result = Transform::Reverse.apply("one two\nthree");
+
+        assert_eq!(result, "three two\none");
+    }
+
+    #[test]
+    fn mirrored_words() {
+        let result = Transform::Mirror.apply("one two\nthree");
+
+        assert_eq!(result, "eno owt\neerht");
+    }
+
+    #[test]
+    fn leetspeak() {
+        let result = Transform::Leet.apply("Leet speak");
+
+        assert_eq!(result, "L337 5p34k");
+    }
+
+    #[test]
+    fn random_capitalization_only_changes_the_case() {
+        let result = Transform::Caps.apply("random capitalization");
+
+        assert_eq!(result.to_lowercase(), "random capitalization");
+    }
+
+    #[test]
+    fn transforms_are_composed_in_order() {
+        let result = apply_transforms(
+            "hello world",
+            &[Transform::Reverse, Transform::Mirror, Transform::Memory],
+        );
+
+        assert_eq!(result, "dlrow olleh");
+    }
+
+    #[test]
+    fn memory_preview_grows_with_the_text() {
+        let result = memory_preview("a b c");
+
+        assert_eq!(result, Duration::from_millis(1200));
+    }
+}
@@ -1,4 +1,7 @@
-use std::io::{self, stdout, Stdout};
+use std::{
+    io::{self, stdout, Stdout},
+    panic,
+};
 
 use crossterm::{
     execute,
@@ -12,6 +15,14 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 pub fn init() -> io::Result<Tui> {
+    restore_on_panic();
+    // leave the terminal as it was when only part of the setup went through
+    setup().inspect_err(|_| {
+        let _ = restore();
+    })
+}
+
+fn setup() -> io::Result<Tui> {
     execute!(stdout(), EnterAlternateScreen)?;
     enable_raw_mode()?;
     Terminal::new(CrosstermBackend::new(stdout()))
@@ -23,3 +34,12 @@ pub fn restore() -> io::Result<()> {
     disable_raw_mode()?;
     Ok(())
 }
+
+/// Restore the terminal before a panic is reported, so the message can be read
+fn restore_on_panic() {
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        report(info);
+    }));
+}
@@ -1,21 +1,57 @@
-use crossterm::event::KeyCode;
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyModifiers};
 use itertools::EitherOrBoth;
 use std::{error::Error, fmt::Display, time::Instant};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct KeyEventSource {
     pub key: KeyCode,
+    pub modifiers: KeyModifiers,
     pub timestamp: Instant,
 }
 
-#[derive(PartialEq, Debug)]
-pub struct InputTargetChar(pub Option<char>, pub Option<char>);
-
 pub type WordMatch = Vec<EitherOrBoth<char>>;
 
 #[derive(PartialEq, Debug)]
 pub struct WordGameStatus(pub Vec<WordMatch>, pub WordMatch, pub Option<String>);
 
+/// Options that change how the user input is matched against the target sentence
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TypingOptions {
+    /// Fill the indentation of a new line as soon as the user presses Enter
+    pub skip_indentation: bool,
+    pub stop_on_error: StopOnError,
+    pub confidence: Confidence,
+    /// Maximum number of characters that can be typed past the end of a word
+    pub max_extra_chars: Option<usize>,
+    /// Type freely without a target sentence, whatever is typed being the text
+    pub zen: bool,
+}
+
+/// Whether wrong keys stop the user from moving forward
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum StopOnError {
+    /// Wrong keys are typed as any other key
+    #[default]
+    Off,
+    /// Wrong keys are not accepted
+    Letter,
+    /// The user can't move past a word until it's typed correctly
+    Word,
+}
+
+/// How much the user is allowed to correct their input
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Confidence {
+    /// Backspace deletes anything, including previous words
+    #[default]
+    Off,
+    /// Backspace can't go back to previous words
+    On,
+    /// Backspace is disabled
+    Max,
+}
+
 #[derive(Debug)]
 pub struct GameFinished;
 
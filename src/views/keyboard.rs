@@ -0,0 +1,154 @@
+use itertools::Itertools;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use crate::layout::{Finger, Layout};
+
+/// Keyboard drawn under the text, highlighting the key expected next with the color of the
+/// finger pressing it and flashing the key of a wrong press
+#[derive(Debug)]
+pub struct Keyboard<'a> {
+    layout: &'a Layout,
+    next_char: Option<char>,
+    wrong_char: Option<char>,
+}
+
+/// Key drawn on the keyboard besides the keys of the layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpecialKey {
+    Tab,
+    Enter,
+    LeftShift,
+    RightShift,
+    Space,
+}
+
+impl<'a> Keyboard<'a> {
+    /// Lines taken by the keyboard
+    pub const HEIGHT: u16 = 5;
+
+    pub fn new(layout: &'a Layout) -> Self {
+        Self {
+            layout,
+            next_char: None,
+            wrong_char: None,
+        }
+    }
+
+    pub fn with_next_char(mut self, next_char: Option<char>) -> Self {
+        self.next_char = next_char;
+        self
+    }
+
+    pub fn with_wrong_char(mut self, wrong_char: Option<char>) -> Self {
+        self.wrong_char = wrong_char;
+        self
+    }
+
+    /// Style of the key at `column` of `row`
+    fn key_style(&self, row: usize, column: usize) -> Style {
+        let is_key = |c: Option<char>| {
+            c.and_then(|c| self.layout.position(c))
+                .is_some_and(|position| position.row == row && position.column == column)
+        };
+        let finger = self.layout.finger(row, column);
+
+        if is_key(self.wrong_char) {
+            Style::new().bg(Color::Rgb(150, 30, 30)).fg(Color::White)
+        } else if is_key(self.next_char) {
+            Style::new().bg(finger_color(finger)).fg(Color::Black)
+        } else {
+            Style::new().fg(finger_color(finger))
+        }
+    }
+
+    fn special_key_style(&self, key: SpecialKey) -> Style {
+        let shifted_finger = self
+            .next_char
+            .filter(|&c| self.layout.position(c).is_some_and(|key| key.shifted))
+            .and_then(|c| Finger::for_char(self.layout, c));
+        let (finger, is_next) = match key {
+            SpecialKey::Tab => (Finger::LeftPinky, self.next_char == Some('\t')),
+            SpecialKey::Enter => (Finger::RightPinky, self.next_char == Some('\n')),
+            SpecialKey::Space => (Finger::Thumb, self.next_char == Some(' ')),
+            // Shift is pressed with the hand not pressing the key
+            SpecialKey::LeftShift => (
+                Finger::LeftPinky,
+                shifted_finger.is_some_and(|finger| !finger.is_left_hand()),
+            ),
+            SpecialKey::RightShift => (
+                Finger::RightPinky,
+                shifted_finger.is_some_and(|finger| finger.is_left_hand()),
+            ),
+        };
+
+        if is_next {
+            Style::new().bg(finger_color(finger)).fg(Color::Black)
+        } else {
+            Style::new().fg(Color::DarkGray)
+        }
+    }
+
+    fn special_key(&self, label: &'static str, key: SpecialKey) -> Span<'static> {
+        Span::styled(label, self.special_key_style(key))
+    }
+}
+
+impl Widget for Keyboard<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = self
+            .layout
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(row, keys)| {
+                keys.unshifted
+                    .iter()
+                    .enumerate()
+                    .map(|(column, key)| {
+                        Span::styled(format!(" {} ", key), self.key_style(row, column))
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        // stagger the rows like on a keyboard, with the special keys around them
+        if let [_, top_row, home_row, bottom_row] = &mut lines[..] {
+            top_row.insert(0, self.special_key(" ⇥   ", SpecialKey::Tab));
+            home_row.insert(0, Span::raw("      "));
+            home_row.push(self.special_key("  ⏎  ", SpecialKey::Enter));
+            bottom_row.insert(0, self.special_key("   ⇧   ", SpecialKey::LeftShift));
+            bottom_row.push(self.special_key("    ⇧   ", SpecialKey::RightShift));
+        }
+        lines.push(vec![
+            Span::raw("            "),
+            self.special_key("                    ", SpecialKey::Space)
+                .underlined(),
+        ]);
+
+        let lines = lines.into_iter().map(Line::from).collect_vec();
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+        let centered_area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            width: width.min(area.width),
+            ..area
+        };
+        Paragraph::new(lines).render(centered_area, buf);
+    }
+}
+
+/// Color of the keys pressed by a finger, mirrored between hands
+fn finger_color(finger: Finger) -> Color {
+    match finger {
+        Finger::LeftPinky | Finger::RightPinky => Color::Rgb(170, 110, 200),
+        Finger::LeftRing | Finger::RightRing => Color::Rgb(90, 140, 220),
+        Finger::LeftMiddle | Finger::RightMiddle => Color::Rgb(90, 190, 120),
+        Finger::LeftIndex | Finger::RightIndex => Color::Rgb(220, 180, 80),
+        Finger::Thumb => Color::Rgb(90, 190, 200),
+    }
+}
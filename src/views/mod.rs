@@ -1,3 +1,6 @@
+pub mod keyboard;
 pub mod run;
 pub mod statistics;
+#[cfg(test)]
+pub mod test_harness;
 pub mod typing_playground;
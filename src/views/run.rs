@@ -1,8 +1,14 @@
 use std::io;
 
-use crate::tui::Tui;
+use ratatui::{backend::Backend, Terminal};
+
+use crate::input::InputSource;
 
 pub trait Runnable {
-    /// Method to run this view in the provided terminal
-    fn run(&mut self, terminal: &mut Tui) -> io::Result<()>;
+    /// Method to run this view in the provided terminal, reading the keys from `input`
+    fn run<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        input: &mut dyn InputSource,
+    ) -> io::Result<()>;
 }
@@ -1,63 +1,229 @@
-use std::io;
+use std::{cmp::Reverse, io, iter, time::Duration};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::KeyCode;
+use itertools::Itertools;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::Color,
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
     widgets::{
         block::{Position, Title},
-        Block, Paragraph, Widget,
+        Block, Paragraph, Row, Table, Widget,
     },
-    Frame,
+    Frame, Terminal,
 };
 
 use crate::{
-    sentences::{build_sentence, verify_sentence_input},
-    tui,
-    types::KeyEventSource,
+    book::BookProgress,
+    failure::FailReason,
+    fingers::FingerAnalytics,
+    input::InputSource,
+    lessons::LessonResult,
+    metrics::{word_breakdown, RunSummary, WordBreakdown},
+    ngrams::NgramTimings,
+    types::{KeyEventSource, TypingOptions},
 };
 
 use super::run::Runnable;
 
+/// Time waited for a key before drawing the results again
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// Number of sequences of each length listed in the results
+const SLOWEST_NGRAMS: usize = 5;
+
+/// Width of the word, typed, time, speed and status columns of the word breakdown
+const WORD_BREAKDOWN_WIDTHS: [Constraint; 5] = [
+    Constraint::Min(10),
+    Constraint::Min(10),
+    Constraint::Length(8),
+    Constraint::Length(8),
+    Constraint::Length(8),
+];
+
+/// Column the per word breakdown is sorted by
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum WordSort {
+    /// Order of the target sentence
+    #[default]
+    Position,
+    /// Time taken, longest first
+    Time,
+    /// Slowest words first
+    Speed,
+    /// Words with errors first
+    Errors,
+}
+
+impl WordSort {
+    const ALL: [Self; 4] = [Self::Position, Self::Time, Self::Speed, Self::Errors];
+
+    /// Sorts of a zen test, where words can't have errors
+    const ZEN: [Self; 3] = [Self::Position, Self::Time, Self::Speed];
+
+    fn index(self, sorts: &[Self]) -> usize {
+        sorts.iter().position(|&sort| sort == self).unwrap()
+    }
+
+    fn next(self, sorts: &[Self]) -> Self {
+        sorts[(self.index(sorts) + 1) % sorts.len()]
+    }
+
+    fn previous(self, sorts: &[Self]) -> Self {
+        sorts[(self.index(sorts) + sorts.len() - 1) % sorts.len()]
+    }
+
+    fn sort(self, breakdown: &mut [WordBreakdown]) {
+        match self {
+            Self::Position => breakdown.sort_by_key(|word| word.position),
+            Self::Time => breakdown.sort_by_key(|word| Reverse(word.duration)),
+            Self::Speed => breakdown.sort_by(|a, b| {
+                let wpm = |word: &WordBreakdown| word.wpm.unwrap_or(f64::INFINITY);
+                wpm(a).total_cmp(&wpm(b))
+            }),
+            Self::Errors => breakdown.sort_by_key(|word| (word.correct, word.position)),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct StatisticsView {
-    target_word: String,
     user_events: Vec<KeyEventSource>,
+    summary: RunSummary,
+    /// Words of the test, in the order of `word_sort`
+    word_breakdown: Vec<WordBreakdown>,
+    word_sort: WordSort,
+    /// Index of the first word shown in the breakdown
+    scroll: usize,
+    ngram_timings: NgramTimings,
+    book_progress: Option<BookProgress>,
+    lesson_result: Option<LessonResult>,
+    finger_analytics: Option<FingerAnalytics>,
+    fail_reason: Option<FailReason>,
+    /// Whether the test had no target, so nothing is compared against one
+    zen: bool,
+    next_requested: bool,
     exit: bool,
 }
 
 impl StatisticsView {
-    pub fn new(user_events: Vec<KeyEventSource>, target_word: String) -> Self {
+    pub fn new(
+        user_events: Vec<KeyEventSource>,
+        target_word: String,
+        options: TypingOptions,
+    ) -> Self {
+        let summary = RunSummary::new(&user_events, &target_word, &options);
+        let word_breakdown = word_breakdown(&user_events, &target_word, &options);
+        let ngram_timings = NgramTimings::from_events(&user_events, &target_word, &options);
         Self {
             user_events,
-            target_word,
+            summary,
+            word_breakdown,
+            word_sort: WordSort::default(),
+            scroll: 0,
+            ngram_timings,
+            book_progress: None,
+            lesson_result: None,
+            finger_analytics: None,
+            fail_reason: None,
+            zen: false,
+            next_requested: false,
             exit: false,
         }
     }
 
+    /// Show the progress through a book and let the user continue with the next test
+    pub fn with_book_progress(mut self, book_progress: BookProgress) -> Self {
+        self.book_progress = Some(book_progress);
+        self
+    }
+
+    /// Tell whether the lesson of the course was passed
+    pub fn with_lesson_result(mut self, lesson_result: LessonResult) -> Self {
+        self.lesson_result = Some(lesson_result);
+        self
+    }
+
+    /// Show how the keys were spread over the fingers
+    pub fn with_finger_analytics(mut self, finger_analytics: FingerAnalytics) -> Self {
+        self.finger_analytics = Some(finger_analytics);
+        self
+    }
+
+    /// Report the test as failed because of `fail_reason`
+    pub fn with_fail_reason(mut self, fail_reason: Option<FailReason>) -> Self {
+        self.fail_reason = fail_reason;
+        self.summary = self.summary.clone().with_fail_reason(fail_reason);
+        self
+    }
+
+    /// Hide the statistics comparing the input with a target, for tests typed freely
+    pub fn with_zen(mut self) -> Self {
+        self.zen = true;
+        self
+    }
+
+    /// Whether the user asked for the next test instead of exiting
+    pub fn next_requested(&self) -> bool {
+        self.next_requested
+    }
+
     fn render_frame(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.size());
     }
 
-    fn handle_events(&mut self) -> Option<()> {
-        match event::read().ok()? {
-            Event::Key(key_event)
-                if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Esc =>
-            {
+    fn word_sorts(&self) -> &'static [WordSort] {
+        if self.zen {
+            &WordSort::ZEN
+        } else {
+            &WordSort::ALL
+        }
+    }
+
+    fn sort_words(&mut self, word_sort: WordSort) {
+        self.word_sort = word_sort;
+        self.word_sort.sort(&mut self.word_breakdown);
+        self.scroll = 0;
+    }
+
+    fn handle_events(&mut self, input: &mut dyn InputSource) -> io::Result<()> {
+        if let Some(event) = input.next_key(TICK_RATE)? {
+            self.handle_key(event.key);
+        }
+
+        Ok(())
+    }
+
+    /// Scroll and sort the words, or exit
+    fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Enter if self.book_progress.is_some() => {
+                self.next_requested = true;
                 self.exit = true;
             }
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => {
+                self.scroll = (self.scroll + 1).min(self.word_breakdown.len().saturating_sub(1))
+            }
+            KeyCode::Left => self.sort_words(self.word_sort.previous(self.word_sorts())),
+            KeyCode::Right => self.sort_words(self.word_sort.next(self.word_sorts())),
             _ => {}
         };
-
-        Some(())
     }
 }
 
 impl Runnable for StatisticsView {
-    fn run(&mut self, terminal: &mut tui::Tui) -> io::Result<()> {
+    fn run<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        input: &mut dyn InputSource,
+    ) -> io::Result<()> {
         loop {
             terminal.draw(|frame| self.render_frame(frame))?;
-            self.handle_events();
+            self.handle_events(input)?;
 
             if self.exit {
                 break;
@@ -67,21 +233,152 @@ impl Runnable for StatisticsView {
     }
 }
 
+impl StatisticsView {
+    /// Table of the words of the test, scrolled with Up and Down and sorted with Left and
+    /// Right
+    fn render_word_breakdown(&self, area: Rect, buf: &mut Buffer) {
+        // a zen test is its own target, only the typed words are shown
+        let (columns, widths) = if self.zen {
+            (1..4, &WORD_BREAKDOWN_WIDTHS[1..4])
+        } else {
+            (0..5, &WORD_BREAKDOWN_WIDTHS[..])
+        };
+
+        let header = ["Word", "Typed", "Time", "WPM", "Status"]
+            .into_iter()
+            .enumerate()
+            .map(|(column, label)| {
+                let sort_column = match self.word_sort {
+                    WordSort::Position => {
+                        if self.zen {
+                            1
+                        } else {
+                            0
+                        }
+                    }
+                    WordSort::Time => 2,
+                    WordSort::Speed => 3,
+                    WordSort::Errors => 4,
+                };
+                if column == sort_column {
+                    format!("{} ▾", label)
+                } else {
+                    label.to_string()
+                }
+            })
+            .collect_vec();
+
+        let rows = self.word_breakdown.iter().skip(self.scroll).map(|word| {
+            let cells = [
+                word.target.clone(),
+                word.typed.clone(),
+                format!("{:.2}s", word.duration.as_secs_f64()),
+                word.wpm
+                    .map(|wpm| format!("{:.1}", wpm))
+                    .unwrap_or_else(|| "-".to_string()),
+                if word.correct { "ok" } else { "error" }.to_string(),
+            ];
+            Row::new(cells[columns.clone()].to_vec()).style(if word.correct {
+                Style::default()
+            } else {
+                Style::default().fg(Color::Red)
+            })
+        });
+
+        Table::new(rows, widths.to_vec())
+            .header(
+                Row::new(header[columns].to_vec())
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .render(area, buf);
+    }
+}
+
+impl StatisticsView {
+    /// Bigrams and trigrams of the test with the highest average time
+    fn render_slowest_ngrams(&self, area: Rect, buf: &mut Buffer) {
+        let lines = [(2, "Slowest bigrams"), (3, "Slowest trigrams")]
+            .into_iter()
+            .flat_map(|(length, title)| {
+                let slowest = self.ngram_timings.slowest(length, SLOWEST_NGRAMS);
+                iter::once(Line::styled(title, Modifier::BOLD)).chain(
+                    slowest
+                        .into_iter()
+                        .map(|(sequence, timing)| {
+                            Line::from(format!(
+                                "{:<6}{:>6}ms",
+                                sequence,
+                                timing.average().as_millis()
+                            ))
+                        })
+                        .chain(iter::once(Line::default())),
+                )
+            })
+            .collect_vec();
+
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+/// Load, accuracy and average time of every finger used. The accuracy is left out when
+/// there was no target to compare the keys with
+fn render_finger_analytics(
+    analytics: &FingerAnalytics,
+    show_accuracy: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let rows = analytics.fingers.iter().map(|(finger, stats)| {
+        Row::new(vec![
+            finger.name().to_string(),
+            format!("{:.1}%", analytics.load(*finger)),
+            if show_accuracy {
+                format!("{:.1}%", stats.accuracy())
+            } else {
+                "-".to_string()
+            },
+            format!("{}ms", stats.average_time().as_millis()),
+        ])
+    });
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(13),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new(vec!["Finger", "Load", "Acc.", "Time"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::new().title(format!(
+        "Same finger bigrams: {:.1}%",
+        analytics.same_finger_bigram_rate()
+    )))
+    .render(area, buf);
+}
+
 impl Widget for &StatisticsView {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    fn render(self, area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
         let title = Title::from("Last Run");
-        let instructions = Title::from("Press <ESC> to exit");
+        let instructions = Title::from(if self.book_progress.is_some() {
+            "<↑↓> scroll, <←→> sort, <ENTER> next test, <ESC> exit"
+        } else {
+            "<↑↓> scroll, <←→> sort, <ESC> exit"
+        });
         let vertical_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![Constraint::Length(3), Constraint::Percentage(100)])
             .split(area);
 
         // render header
-        let game_status =
-            verify_sentence_input(&build_sentence(&self.user_events), &self.target_word);
+        let game_status = self.summary.completed;
         Block::bordered()
             .title(title.alignment(Alignment::Center))
             .render(vertical_layout[0], buf);
@@ -92,17 +389,20 @@ impl Widget for &StatisticsView {
         Paragraph::new(format!("Total pressed keys: {}", self.user_events.len()))
             .alignment(Alignment::Left)
             .render(header_layout[0], buf);
-        Paragraph::new(format!(
-            "Game status: {}",
-            if game_status { "succeed" } else { "failure" }
-        ))
-        .style(if game_status {
-            Color::Green
-        } else {
-            Color::Red
-        })
-        .alignment(Alignment::Right)
-        .render(header_layout[1], buf);
+        let game_status_text = match (game_status, self.fail_reason) {
+            (_, None) if self.zen => "typed freely".to_string(),
+            (true, _) => "succeed".to_string(),
+            (false, Some(fail_reason)) => format!("failure, {}", fail_reason),
+            (false, None) => "failure, the text doesn't match".to_string(),
+        };
+        Paragraph::new(format!("Game status: {}", game_status_text))
+            .style(if game_status {
+                Color::Green
+            } else {
+                Color::Red
+            })
+            .alignment(Alignment::Right)
+            .render(header_layout[1], buf);
 
         // render body
         let body = Block::bordered().title(
@@ -111,7 +411,179 @@ impl Widget for &StatisticsView {
                 .position(Position::Bottom),
         );
 
+        let summary = &self.summary;
+        let speed = if self.zen {
+            format!(
+                "Speed: {:.1} wpm, time: {:.1}s",
+                summary.wpm,
+                summary.duration.as_secs_f64()
+            )
+        } else {
+            format!(
+                "Speed: {:.1} wpm, accuracy: {:.1}%, time: {:.1}s",
+                summary.wpm,
+                summary.accuracy,
+                summary.duration.as_secs_f64()
+            )
+        };
+        let mut lines = vec![
+            Line::from(speed),
+            Line::from(format!(
+                "Consistency: {:.1}%, burst: {:.1} wpm",
+                summary.consistency, summary.burst_wpm
+            )),
+        ];
+        if !summary.slowest_words.is_empty() {
+            lines.push(Line::from(format!(
+                "Slowest words: {}",
+                summary
+                    .slowest_words
+                    .iter()
+                    .map(|speed| format!("{} ({:.1} wpm)", speed.word, speed.wpm))
+                    .join(", ")
+            )));
+        }
+
+        if let Some(lesson_result) = &self.lesson_result {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                lesson_result.to_string(),
+                if lesson_result.passed {
+                    Color::Green
+                } else {
+                    Color::Yellow
+                },
+            ));
+        }
+
+        if let Some(book_progress) = &self.book_progress {
+            let averages = &book_progress.chapter_averages;
+            lines.push(Line::default());
+            lines.push(Line::from(format!(
+                "{}: test {} of {}",
+                book_progress.chapter, book_progress.test, book_progress.total_tests
+            )));
+            lines.push(Line::from(format!(
+                "Chapter history: {} tests, {:.1} wpm, {:.1}% accuracy{}",
+                averages.tests,
+                averages.wpm,
+                averages.accuracy,
+                averages
+                    .consistency
+                    .map(|consistency| format!(", {:.1}% consistency", consistency))
+                    .unwrap_or_default()
+            )));
+        }
+
+        let body_area = body.inner(vertical_layout[1]);
         body.render(vertical_layout[1], buf);
-        // block.render(area, buf);
+        let body_layout = Layout::vertical(vec![
+            Constraint::Length(lines.len() as u16 + 1),
+            Constraint::Min(0),
+        ])
+        .split(body_area);
+        Paragraph::new(lines).render(body_layout[0], buf);
+        let tables_layout = Layout::horizontal(vec![
+            Constraint::Min(0),
+            Constraint::Length(24),
+            Constraint::Length(if self.finger_analytics.is_some() {
+                40
+            } else {
+                0
+            }),
+        ])
+        .split(body_layout[1]);
+        self.render_word_breakdown(tables_layout[0], buf);
+        self.render_slowest_ngrams(tables_layout[1], buf);
+        if let Some(finger_analytics) = &self.finger_analytics {
+            render_finger_analytics(finger_analytics, !self.zen, tables_layout[2], buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod statistics_view_tests {
+    use std::time::Duration;
+
+    use crossterm::event::KeyCode;
+    use ratatui::style::Color;
+
+    use crate::{
+        input::InputSource,
+        types::TypingOptions,
+        views::{
+            run::Runnable,
+            test_harness::{assert_snapshot, draw, test_terminal, ScriptedEvents},
+        },
+    };
+
+    use super::StatisticsView;
+
+    fn statistics_view(target: &str, script: ScriptedEvents) -> StatisticsView {
+        StatisticsView::new(
+            script.events(),
+            target.to_string(),
+            TypingOptions::default(),
+        )
+    }
+
+    #[test]
+    fn completed_run() {
+        let view = statistics_view(
+            "the lazy dog",
+            ScriptedEvents::new().type_text("the lazy dog"),
+        );
+
+        let buffer = draw(90, 14, |frame| view.render_frame(frame));
+
+        assert_snapshot("statistics_completed_run", &buffer);
+    }
+
+    #[test]
+    fn run_with_errors() {
+        let view = statistics_view(
+            "the lazy dog",
+            ScriptedEvents::new().type_text("the lasy dgo"),
+        );
+
+        let buffer = draw(90, 14, |frame| view.render_frame(frame));
+
+        assert_snapshot("statistics_run_with_errors", &buffer);
+        // rows of the word breakdown start under the header of the table
+        assert_eq!(buffer.get(1, 9).fg, Color::Reset);
+        assert_eq!(buffer.get(1, 10).fg, Color::Red);
+    }
+
+    #[test]
+    fn words_are_sorted_by_time() {
+        let mut view = statistics_view(
+            "the lazy dog",
+            ScriptedEvents::new()
+                .type_text("the ")
+                .wait(Duration::from_secs(2))
+                .type_text("lazy dog"),
+        );
+
+        view.handle_key(KeyCode::Right);
+        let buffer = draw(90, 14, |frame| view.render_frame(frame));
+
+        assert_snapshot("statistics_words_are_sorted_by_time", &buffer);
+    }
+
+    #[test]
+    fn run_stops_on_esc() {
+        let mut view = statistics_view("ab", ScriptedEvents::new().type_text("ab"));
+        let mut input = ScriptedEvents::new()
+            .press(KeyCode::Right)
+            .press(KeyCode::Esc)
+            .press(KeyCode::Down)
+            .input();
+
+        let result = view.run(&mut test_terminal(90, 14), &mut input);
+
+        assert!(result.is_ok());
+        assert!(!view.next_requested());
+        // the last key is left unread
+        assert!(input.next_key(Duration::ZERO).is_ok());
     }
 }
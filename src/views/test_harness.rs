@@ -0,0 +1,199 @@
+//! Helpers to script the keys of a test, drive the views with them and compare what they
+//! render with the snapshots stored in `src/views/snapshots`
+//!
+//! Run the tests with `UPDATE_SNAPSHOTS=1` to write the snapshots after changing a view
+
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use itertools::Itertools;
+use ratatui::{backend::TestBackend, buffer::Buffer, Frame, Terminal};
+
+use crate::{
+    input::{Clock, InputSource},
+    types::KeyEventSource,
+};
+
+/// Time between two keys of a script, unless it waits longer
+const KEY_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Keys pressed one after the other at a steady pace, so the views render the same results
+/// on every run
+#[derive(Debug)]
+pub struct ScriptedEvents {
+    start: Instant,
+    elapsed: Duration,
+    events: Vec<KeyEventSource>,
+}
+
+impl ScriptedEvents {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Duration::ZERO,
+            events: vec![],
+        }
+    }
+
+    pub fn press(self, key: KeyCode) -> Self {
+        self.press_with(key, KeyModifiers::NONE)
+    }
+
+    /// Press `key` while holding `modifiers`
+    pub fn press_with(mut self, key: KeyCode, modifiers: KeyModifiers) -> Self {
+        if !self.events.is_empty() {
+            self.elapsed += KEY_INTERVAL;
+        }
+        self.events.push(KeyEventSource {
+            key,
+            modifiers,
+            timestamp: self.start + self.elapsed,
+        });
+        self
+    }
+
+    /// Press `key` `millis` milliseconds after the start of the script
+    pub fn press_at(mut self, key: KeyCode, millis: u64) -> Self {
+        self.elapsed = Duration::from_millis(millis);
+        self.events.push(KeyEventSource {
+            key,
+            modifiers: KeyModifiers::NONE,
+            timestamp: self.start + self.elapsed,
+        });
+        self
+    }
+
+    /// Press the key of every character, Enter and Tab included
+    pub fn type_text(self, text: &str) -> Self {
+        text.chars()
+            .fold(self, |script, c| script.press(char_key(c)))
+    }
+
+    /// Press the key of every character, Enter and Tab included, at its time in milliseconds
+    /// since the start of the script
+    pub fn type_at(self, text: &str, millis: &[u64]) -> Self {
+        text.chars().zip(millis).fold(self, |script, (c, &millis)| {
+            script.press_at(char_key(c), millis)
+        })
+    }
+
+    /// Leave `duration` more before the next key
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.elapsed += duration;
+        self
+    }
+
+    pub fn events(self) -> Vec<KeyEventSource> {
+        self.events
+    }
+
+    /// Input giving the keys of the script, one per tick
+    pub fn input(self) -> ScriptedInput {
+        ScriptedInput {
+            clock: SimulatedClock(Cell::new(self.start)),
+            events: self.events.into(),
+        }
+    }
+}
+
+/// Key typing `c`
+fn char_key(c: char) -> KeyCode {
+    match c {
+        '\n' => KeyCode::Enter,
+        '\t' => KeyCode::Tab,
+        c => KeyCode::Char(c),
+    }
+}
+
+/// Clock moved forward by hand
+#[derive(Debug)]
+pub struct SimulatedClock(Cell<Instant>);
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+/// Input of scripted keys, whose clock reaches the time of every key as it's read. It ends
+/// with an error once every key was read
+#[derive(Debug)]
+pub struct ScriptedInput {
+    clock: SimulatedClock,
+    events: VecDeque<KeyEventSource>,
+}
+
+impl InputSource for ScriptedInput {
+    fn next_key(&mut self, _timeout: Duration) -> io::Result<Option<KeyEventSource>> {
+        let event = self.events.pop_front().ok_or_else(|| {
+            io::Error::new(ErrorKind::UnexpectedEof, "every key of the script was read")
+        })?;
+        self.clock.0.set(event.timestamp);
+        Ok(Some(event))
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &self.clock
+    }
+}
+
+/// Terminal of `width` by `height` cells drawing in memory
+pub fn test_terminal(width: u16, height: u16) -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(width, height)).unwrap()
+}
+
+/// Buffer of a terminal of `width` by `height` cells after drawing a frame
+pub fn draw(width: u16, height: u16, render: impl FnOnce(&mut Frame)) -> Buffer {
+    let mut terminal = test_terminal(width, height);
+    terminal.draw(render).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// Text of the buffer, one line per row, without the trailing spaces
+pub fn buffer_lines(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            (area.left()..area.right())
+                .map(|x| buffer.get(x, y).symbol())
+                .join("")
+                .trim_end()
+                .to_string()
+        })
+        .map(|line| format!("{}\n", line))
+        .join("")
+}
+
+/// Compare the text of the buffer with the snapshot called `name`, or write the snapshot
+/// when `UPDATE_SNAPSHOTS` is set
+pub fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/views/snapshots")
+        .join(format!("{}.snap", name));
+    let lines = buffer_lines(buffer);
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, lines).unwrap();
+        return;
+    }
+
+    let snapshot = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "there is no snapshot `{}`, run the tests with UPDATE_SNAPSHOTS=1 to write it",
+            path.display()
+        )
+    });
+    assert_eq!(
+        lines, snapshot,
+        "`{}` changed, run the tests with UPDATE_SNAPSHOTS=1 to update it",
+        name
+    );
+}
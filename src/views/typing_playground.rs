@@ -1,92 +1,247 @@
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use clap::ValueEnum;
+use crossterm::event::KeyCode;
 use itertools::{EitherOrBoth, Itertools};
-use ratatui::layout::Alignment;
-use ratatui::style::Style;
+use ratatui::backend::Backend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Rect};
+use ratatui::style::{Color, Style, Stylize};
 use ratatui::symbols::border;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::block::{Position, Title};
 use ratatui::widgets::{Block, Borders, Paragraph, Widget, Wrap};
-use ratatui::Frame;
+use ratatui::{Frame, Terminal};
 
-use crate::splitter::get_current_game_status;
-use crate::tui;
-use crate::types::{KeyEventSource, WordGameStatus, WordMatch};
+use crate::code::{highlight, Language, TokenKind};
+use crate::dictionary::Direction;
+use crate::engine::TypingEngine;
+use crate::failure::{FailConditions, FailReason};
+use crate::input::InputSource;
+use crate::layout::Layout;
+use crate::splitter::{get_current_game_status, is_end_reached};
+use crate::transforms::{memory_preview, Transform};
+use crate::types::{KeyEventSource, TypingOptions, WordGameStatus, WordMatch};
+use crate::views::keyboard::Keyboard;
 use std::io;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Time waited for a key before checking the fail conditions again
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Time the key of a wrong press stays highlighted on the keyboard
+const WRONG_KEY_FLASH: Duration = Duration::from_millis(300);
 
 use super::run::Runnable;
 
+/// How characters typed past the end of a word are rendered
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Overflow {
+    /// Extra characters are rendered in the text, pushing the following words
+    #[default]
+    Inline,
+    /// Extra characters are hidden and the last letter of the word is highlighted, so the
+    /// text doesn't move
+    Compact,
+}
+
+/// How mistakes are shown while typing. They are counted the same way in every case and
+/// shown on the results
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ErrorDisplay {
+    /// Wrong characters are rendered in red and skipped characters are underlined
+    #[default]
+    Shown,
+    /// The characters typed are rendered as typed, wrong or not, in the same color
+    Hidden,
+    /// The text is rendered as if every character was typed correctly. Characters typed past
+    /// the end of a word have no target to stand for, so they are rendered as typed
+    Blind,
+}
+
 #[derive(Debug, Default)]
 pub struct TypingPlayground {
     events: Vec<KeyEventSource>,
+    /// Keys as pressed on the keyboard, before the layout translates them
+    pressed_keys: Vec<KeyEventSource>,
     target_word: String,
+    options: TypingOptions,
+    /// Token kind of every character of the target word when it's source code
+    highlighting: Option<Vec<TokenKind>>,
+    fail_conditions: FailConditions,
+    fail_reason: Option<FailReason>,
+    overflow: Overflow,
+    error_display: ErrorDisplay,
+    /// Layout typed on the QWERTY keyboard, when it's not QWERTY
+    layout: Option<Layout>,
+    /// Writing direction of the language of the text
+    direction: Direction,
+    show_keyboard: bool,
+    /// Character of the last wrong press and when it was typed
+    wrong_key: Option<(char, Instant)>,
+    /// Transforms of the text, of which only the ones hiding it matter here
+    transforms: Vec<Transform>,
+    /// When the text was first shown
+    started: Option<Instant>,
+    /// Time of the clock of the input when the keys were last read
+    now: Option<Instant>,
     exit: bool,
 }
 
 impl TypingPlayground {
-    pub fn new(target_word: String) -> Self {
-        let mut _self = TypingPlayground::default();
-        _self.target_word = target_word;
+    pub fn new(target_word: String, options: TypingOptions) -> Self {
+        Self {
+            target_word,
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Color the characters not typed yet following the syntax of `language`
+    pub fn with_highlighting(mut self, language: Language) -> Self {
+        self.highlighting = Some(highlight(&self.target_word, language));
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn with_error_display(mut self, error_display: ErrorDisplay) -> Self {
+        self.error_display = error_display;
+        self
+    }
+
+    /// Align the text to the right for the languages written from right to left
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Translate the keys pressed on the QWERTY keyboard to `layout`
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Draw a keyboard under the text showing the next key to press
+    pub fn with_keyboard(mut self) -> Self {
+        self.show_keyboard = true;
+        self
+    }
 
-        _self
+    /// Hide the text as the memory and read ahead transforms ask for
+    pub fn with_transforms(mut self, transforms: Vec<Transform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    /// End the test as soon as any of the `fail_conditions` is met
+    pub fn with_fail_conditions(mut self, fail_conditions: FailConditions) -> Self {
+        self.fail_conditions = fail_conditions;
+        self
     }
 
     pub fn get_user_events(&self) -> Vec<KeyEventSource> {
         self.events[..].to_vec()
     }
 
+    /// Keys pressed, as they were before the layout translated them, to replay the test
+    pub fn get_pressed_keys(&self) -> Vec<KeyEventSource> {
+        self.pressed_keys.clone()
+    }
+
+    /// Why the test failed, if a fail condition was met
+    pub fn get_fail_reason(&self) -> Option<FailReason> {
+        self.fail_reason
+    }
+
+    fn check_fail_conditions(&mut self) {
+        let Some(first_event) = self.events.first() else {
+            return;
+        };
+
+        let engine = TypingEngine::from_events(&self.events, &self.target_word, &self.options);
+        self.fail_reason = self
+            .fail_conditions
+            .check(&engine, self.now().duration_since(first_event.timestamp));
+    }
+
     fn render_frame(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.size())
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
-            Event::Key(key_event)
-                if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Esc =>
-            {
-                self.exit = true;
-            }
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.events.push(KeyEventSource {
-                    key: key_event.code,
-                    timestamp: Instant::now(),
-                });
+    /// Remember the key of `event` when it's not the one expected next
+    fn flash_wrong_key(&mut self, event: &KeyEventSource) {
+        let expected_char =
+            TypingEngine::from_events(&self.events, &self.target_word, &self.options)
+                .expected_char();
+        if let KeyCode::Char(c) = event.key {
+            if expected_char != Some(c) {
+                self.wrong_key = Some((c, event.timestamp));
             }
-            _ => {}
-        };
+        }
+    }
+
+    /// Current time, as told by the clock of the input
+    fn now(&self) -> Instant {
+        self.now.unwrap_or_else(Instant::now)
+    }
+
+    fn handle_events(&mut self, input: &mut dyn InputSource) -> io::Result<()> {
+        if let Some(event) = input.next_key(TICK_RATE)? {
+            self.handle_key(event);
+        }
+        self.now = Some(input.clock().now());
 
         Ok(())
     }
+
+    /// Type the key of `event`, or exit on Esc
+    fn handle_key(&mut self, event: KeyEventSource) {
+        if event.key == KeyCode::Esc {
+            self.exit = true;
+            return;
+        }
+
+        self.pressed_keys.push(event.clone());
+        let key = self.layout.as_ref().map_or(event.key, |layout| {
+            layout.remap_key(event.key, event.modifiers)
+        });
+        let event = KeyEventSource { key, ..event };
+        if self.show_keyboard && !self.options.zen && self.error_display == ErrorDisplay::Shown {
+            self.flash_wrong_key(&event);
+        }
+        self.events.push(event);
+    }
+
+    /// Whether the test is over, because a fail condition was met or the whole text was
+    /// typed. Tests without a target only end on Esc
+    fn is_finished(&self) -> bool {
+        if self.fail_reason.is_some() {
+            return true;
+        }
+        is_end_reached(&self.events, &self.target_word, &self.options)
+    }
 }
 
 impl Runnable for TypingPlayground {
-    fn run(&mut self, terminal: &mut tui::Tui) -> io::Result<()> {
+    fn run<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        input: &mut dyn InputSource,
+    ) -> io::Result<()> {
+        self.started = Some(input.clock().now());
+        self.now = self.started;
         loop {
             terminal.draw(|frame| self.render_frame(frame))?;
-            self.handle_events()?;
+            self.handle_events(input)?;
 
             if self.exit {
                 break;
             }
 
-            // check if user has typed last word
-            let game_status = get_current_game_status(&self.events, &self.target_word);
-            if game_status.is_err() {
-                break;
-            }
-            let WordGameStatus(_, current_word, future_words) = game_status.unwrap();
-            if future_words.is_some_and(|word| word.len() > 0) {
-                continue;
-            }
-
-            // there is no remaining letters in target word to fill
-            if current_word
-                .iter()
-                .take_while(|w| w.is_both())
-                .collect::<Vec<_>>()
-                .len()
-                == current_word.len()
-            {
+            self.check_fail_conditions();
+            if self.is_finished() {
                 break;
             }
         }
@@ -94,6 +249,30 @@ impl Runnable for TypingPlayground {
     }
 }
 
+impl TypingPlayground {
+    fn render_keyboard(&self, area: Rect, buf: &mut Buffer) {
+        let qwerty;
+        let layout = match &self.layout {
+            Some(layout) => layout,
+            None => {
+                qwerty = Layout::qwerty();
+                &qwerty
+            }
+        };
+        let next_char = TypingEngine::from_events(&self.events, &self.target_word, &self.options)
+            .expected_char();
+        let wrong_char = self
+            .wrong_key
+            .filter(|(_, timestamp)| self.now().duration_since(*timestamp) < WRONG_KEY_FLASH)
+            .map(|(c, _)| c);
+
+        Keyboard::new(layout)
+            .with_next_char(next_char)
+            .with_wrong_char(wrong_char)
+            .render(area, buf);
+    }
+}
+
 impl Widget for &TypingPlayground {
     /// Render the TypingPlayground widget
     ///
@@ -103,7 +282,11 @@ impl Widget for &TypingPlayground {
         Self: Sized,
     {
         let title = Title::from("MoncliType");
-        let instructions = Title::from("Press <ESC> to exit");
+        let instructions = Title::from(if self.options.zen {
+            "Type freely, press <ESC> to finish"
+        } else {
+            "Press <ESC> to exit"
+        });
 
         let block = Block::default()
             .title(title.alignment(Alignment::Left))
@@ -115,7 +298,7 @@ impl Widget for &TypingPlayground {
             .borders(Borders::ALL)
             .border_set(border::THICK);
 
-        let game_status = get_current_game_status(&self.events, &self.target_word);
+        let game_status = get_current_game_status(&self.events, &self.target_word, &self.options);
         if game_status.is_err() {
             return;
         }
@@ -123,52 +306,171 @@ impl Widget for &TypingPlayground {
         let WordGameStatus(already_written_words, current_word, future_words) =
             game_status.unwrap();
 
-        let already_written_words_vec = already_written_words
-            .iter()
-            .flat_map(|inner| {
-                inner
-                    .iter()
-                    .chain(std::iter::once(&EitherOrBoth::Both(' ', ' ')))
-            })
-            .cloned()
-            .collect_vec();
-        let already_written_words_span = build_word_span(&already_written_words_vec);
-        let current_word_spans = build_word_span(&current_word);
-        let future_words = match future_words {
-            Some(v) => format!(" {}", v),
-            None => String::new(),
-        };
-        let future_words_chars: Vec<EitherOrBoth<char, char>> = future_words
+        let future_words_chars: WordMatch = future_words
+            .unwrap_or_default()
             .chars()
-            .map(|c| EitherOrBoth::Right(c))
+            .map(EitherOrBoth::Right)
             .collect();
-        let future_words_spans = build_word_span(&future_words_chars);
-
-        let all_words = already_written_words_span
+        let all_chars = already_written_words
             .iter()
-            .chain(current_word_spans.iter())
-            .chain(future_words_spans.iter())
+            .flatten()
+            .chain(current_word.iter())
+            .chain(future_words_chars.iter())
             .cloned()
             .collect_vec();
+        let mut all_spans = build_word_span(&all_chars, self.error_display);
+        if let Some(highlighting) = &self.highlighting {
+            highlight_spans(&mut all_spans, &all_chars, highlighting);
+        }
+        let written_chars = already_written_words.iter().map(Vec::len).sum::<usize>();
+        if self.error_display == ErrorDisplay::Shown {
+            mark_missed_chars(&mut all_spans[..written_chars], &all_chars[..written_chars]);
+        }
+        let hidden_chars = if self.transforms.contains(&Transform::Memory)
+            && self.started.is_some_and(|started| {
+                self.now().duration_since(started) >= memory_preview(&self.target_word)
+            }) {
+            written_chars..all_chars.len()
+        } else if self.transforms.contains(&Transform::ReadAhead) {
+            written_chars..written_chars + current_word.len()
+        } else {
+            0..0
+        };
+        hide_chars(
+            &mut all_spans[hidden_chars.clone()],
+            &all_chars[hidden_chars],
+        );
+        if self.overflow == Overflow::Compact {
+            all_spans = compact_extra_chars(
+                all_spans,
+                &all_chars,
+                self.error_display == ErrorDisplay::Shown,
+            );
+        }
 
-        let line = Line::from(all_words);
+        let area = if self.show_keyboard {
+            let areas = ratatui::layout::Layout::vertical([
+                Constraint::Min(0),
+                Constraint::Length(Keyboard::HEIGHT + 1),
+            ])
+            .split(area);
+            self.render_keyboard(areas[1], buf);
+            areas[0]
+        } else {
+            area
+        };
 
-        Paragraph::new(line)
-            .left_aligned()
+        Paragraph::new(split_lines(all_spans))
+            .alignment(match self.direction {
+                Direction::LeftToRight => Alignment::Left,
+                Direction::RightToLeft => Alignment::Right,
+            })
             .block(block)
             .style(
                 Style::new()
                     .bg(ratatui::style::Color::Rgb(10, 10, 10))
                     .fg(ratatui::style::Color::Yellow),
             )
-            .wrap(Wrap { trim: true })
+            .wrap(Wrap {
+                trim: self.highlighting.is_none(),
+            })
             .render(area, buf);
     }
 }
 
+/// Split the spans in lines on every new line character. New lines are rendered as a
+/// visible symbol at the end of the line, so the user knows Enter must be pressed
+fn split_lines(spans: Vec<Span<'_>>) -> Vec<Line<'_>> {
+    let mut lines = vec![];
+    let mut current_line = vec![];
+
+    for span in spans {
+        if span.content == "\n" {
+            current_line.push(Span::styled("↵", span.style));
+            lines.push(Line::from(std::mem::take(&mut current_line)));
+        } else {
+            current_line.push(span);
+        }
+    }
+    lines.push(Line::from(current_line));
+
+    lines
+}
+
+/// Color the spans of characters not typed yet following the token kind of the target
+/// character they stand for
+fn highlight_spans(spans: &mut [Span], chars: &WordMatch, highlighting: &[TokenKind]) {
+    let mut target_index = 0;
+    for (span, pair_of_chars) in spans.iter_mut().zip(chars) {
+        if let EitherOrBoth::Right(_) = pair_of_chars {
+            if let Some(kind) = highlighting.get(target_index) {
+                *span = span.clone().fg(token_color(kind));
+            }
+        }
+        if !pair_of_chars.is_left() {
+            target_index += 1;
+        }
+    }
+}
+
+/// Mark the characters of already written words that were skipped, so they don't look
+/// like text still to be typed
+fn mark_missed_chars(spans: &mut [Span], chars: &[EitherOrBoth<char>]) {
+    for (span, pair_of_chars) in spans.iter_mut().zip(chars) {
+        if pair_of_chars.is_right() {
+            *span = span.clone().fg(Color::Rgb(120, 40, 40)).underlined();
+        }
+    }
+}
+
+/// Blank out the characters not typed yet, keeping their width so the text doesn't move.
+/// Whitespace stays visible, so the lines keep their shape
+fn hide_chars(spans: &mut [Span], chars: &[EitherOrBoth<char>]) {
+    for (span, pair_of_chars) in spans.iter_mut().zip(chars) {
+        if let EitherOrBoth::Right(c) = pair_of_chars {
+            if !c.is_whitespace() {
+                *span = Span::styled(" ".repeat(span.width()), span.style);
+            }
+        }
+    }
+}
+
+/// Remove the spans of characters typed past the end of a word, highlighting the last
+/// letter of the word instead when errors are shown
+fn compact_extra_chars<'a>(
+    spans: Vec<Span<'a>>,
+    chars: &[EitherOrBoth<char>],
+    highlight: bool,
+) -> Vec<Span<'a>> {
+    let mut compacted: Vec<Span> = vec![];
+    for (span, pair_of_chars) in spans.into_iter().zip(chars) {
+        if !pair_of_chars.is_left() {
+            compacted.push(span);
+        } else if let Some(last_letter) = compacted.last_mut().filter(|_| highlight) {
+            *last_letter = last_letter.clone().bg(Color::Rgb(120, 20, 20));
+        }
+    }
+
+    compacted
+}
+
+/// Dimmed color of a token kind, so untyped code stays darker than typed code
+fn token_color(kind: &TokenKind) -> Color {
+    match kind {
+        TokenKind::Keyword => Color::Rgb(90, 60, 120),
+        TokenKind::String => Color::Rgb(50, 100, 50),
+        TokenKind::Comment => Color::Rgb(70, 70, 70),
+        TokenKind::Number => Color::Rgb(110, 80, 40),
+        TokenKind::Punctuation => Color::Rgb(80, 80, 100),
+        TokenKind::Plain => Color::Rgb(50, 50, 50),
+    }
+}
+
 /// Build a span for a word. The result is a vector of spans, one for each character
-fn build_word_span(word: &WordMatch) -> Vec<Span> {
-    word.iter().map(build_span_char).collect_vec()
+fn build_word_span(word: &WordMatch, error_display: ErrorDisplay) -> Vec<Span<'_>> {
+    word.iter()
+        .map(|pair_of_chars| build_span_char(pair_of_chars, error_display))
+        .collect_vec()
 }
 
 /// Build a span for a character
@@ -179,10 +481,17 @@ fn build_word_span(word: &WordMatch) -> Vec<Span> {
 /// If the character is only in the target word, it will be grey
 ///
 /// If the character is only in the user input, it will be red
-fn build_span_char(pair_of_chars: &EitherOrBoth<char>) -> Span {
+///
+/// When errors are hidden, typed characters are all white and rendered as typed. In blind
+/// mode they are all white too, but wrong characters are rendered as the target character,
+/// extra characters staying as typed
+///
+/// Tabs are rendered as a visible symbol padded to the width of a tab stop
+fn build_span_char(pair_of_chars: &EitherOrBoth<char>, error_display: ErrorDisplay) -> Span<'_> {
     let color = match pair_of_chars {
-        EitherOrBoth::Left(_) => ratatui::style::Color::Red,
         EitherOrBoth::Right(_) => ratatui::style::Color::Rgb(50, 50, 50),
+        _ if error_display != ErrorDisplay::Shown => ratatui::style::Color::White,
+        EitherOrBoth::Left(_) => ratatui::style::Color::Red,
         EitherOrBoth::Both(a, b) => {
             if a == b {
                 ratatui::style::Color::White
@@ -192,9 +501,191 @@ fn build_span_char(pair_of_chars: &EitherOrBoth<char>) -> Span {
         }
     };
     let letter = match pair_of_chars {
+        EitherOrBoth::Both(a, _) if error_display == ErrorDisplay::Hidden => a,
         EitherOrBoth::Both(_, b) => b,
+        // extra characters are rendered as typed in every mode, even blind
         EitherOrBoth::Left(v) => v,
         EitherOrBoth::Right(v) => v,
     };
-    Span::styled(letter.to_string(), Style::default().fg(color))
+    let letter = match letter {
+        '\t' => "⇥   ".to_string(),
+        _ => letter.to_string(),
+    };
+    Span::styled(letter, Style::default().fg(color))
+}
+
+#[cfg(test)]
+mod typing_playground_tests {
+    use crossterm::event::KeyCode;
+    use ratatui::style::Color;
+
+    use std::time::Duration;
+
+    use crate::{
+        code::Language,
+        failure::{FailConditions, FailReason},
+        layout::load_layout,
+        types::{KeyEventSource, TypingOptions},
+        views::{
+            run::Runnable,
+            test_harness::{assert_snapshot, draw, test_terminal, ScriptedEvents},
+        },
+    };
+
+    use super::{ErrorDisplay, TypingPlayground};
+
+    fn playground(target: &str, script: ScriptedEvents) -> TypingPlayground {
+        script.events().into_iter().fold(
+            TypingPlayground::new(target.to_string(), TypingOptions::default()),
+            |mut playground, event| {
+                playground.handle_key(event);
+                playground
+            },
+        )
+    }
+
+    #[test]
+    fn long_text_wraps() {
+        let playground = playground(
+            "the quick brown fox jumps over the lazy dog",
+            ScriptedEvents::new().type_text("the quick"),
+        );
+
+        let buffer = draw(24, 6, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_long_text_wraps", &buffer);
+    }
+
+    #[test]
+    fn errors_are_red() {
+        let playground = playground("hello world", ScriptedEvents::new().type_text("hallo wo"));
+
+        let buffer = draw(24, 3, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_errors_are_red", &buffer);
+        assert_eq!(buffer.get(2, 1).fg, Color::Red);
+        assert_eq!(buffer.get(1, 1).fg, Color::White);
+    }
+
+    #[test]
+    fn blind_mode_renders_errors_as_correct() {
+        let playground = playground("hello world", ScriptedEvents::new().type_text("hallo wo"))
+            .with_error_display(ErrorDisplay::Blind);
+
+        let buffer = draw(24, 3, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_blind_errors", &buffer);
+        assert_eq!(buffer.get(2, 1).symbol(), "e");
+        assert_eq!(buffer.get(2, 1).fg, Color::White);
+        assert_eq!(buffer.get(1, 1).fg, Color::White);
+        assert_eq!(buffer.get(9, 1).fg, Color::Rgb(50, 50, 50));
+    }
+
+    #[test]
+    fn blind_mode_renders_extras_as_typed() {
+        let playground = playground("hello world", ScriptedEvents::new().type_text("hellox wo"))
+            .with_error_display(ErrorDisplay::Blind);
+
+        let buffer = draw(24, 3, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_blind_extras", &buffer);
+        assert_eq!(buffer.get(6, 1).symbol(), "x");
+        assert_eq!(buffer.get(6, 1).fg, Color::White);
+    }
+
+    #[test]
+    fn hidden_errors_are_rendered_as_typed() {
+        let playground = playground("hello world", ScriptedEvents::new().type_text("hallo wo"))
+            .with_error_display(ErrorDisplay::Hidden);
+
+        let buffer = draw(24, 3, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_hidden_errors", &buffer);
+        assert_eq!(buffer.get(2, 1).symbol(), "a");
+        assert_eq!(buffer.get(2, 1).fg, Color::White);
+        assert_eq!(buffer.get(1, 1).fg, Color::White);
+    }
+
+    #[test]
+    fn code_keeps_new_lines_and_indentation() {
+        let playground = playground(
+            "fn main() {\n    run();\n}",
+            ScriptedEvents::new().type_text("fn main() {\n  "),
+        )
+        .with_highlighting(Language::Rust);
+
+        let buffer = draw(24, 5, |frame| playground.render_frame(frame));
+
+        assert_snapshot("playground_code", &buffer);
+    }
+
+    #[test]
+    fn test_finishes_once_the_text_is_typed() {
+        let script = ScriptedEvents::new().type_text("hello wor");
+        let playground = playground("hello world", script.type_text("ld"));
+
+        assert!(playground.is_finished());
+    }
+
+    #[test]
+    fn esc_exits() {
+        let playground = playground(
+            "hello world",
+            ScriptedEvents::new().type_text("he").press(KeyCode::Esc),
+        );
+
+        assert!(playground.exit);
+        assert_eq!(playground.get_user_events().len(), 2);
+    }
+
+    #[test]
+    fn pressed_keys_are_kept_before_the_layout() {
+        let dvorak = load_layout("dvorak").unwrap();
+        let playground = ScriptedEvents::new()
+            .type_text("sdf")
+            .events()
+            .into_iter()
+            .fold(
+                TypingPlayground::new("oeu".to_string(), TypingOptions::default())
+                    .with_layout(dvorak),
+                |mut playground, event| {
+                    playground.handle_key(event);
+                    playground
+                },
+            );
+
+        let keys = |events: Vec<KeyEventSource>| events.into_iter().map(|event| event.key);
+        assert!(keys(playground.get_user_events()).eq("oeu".chars().map(KeyCode::Char)));
+        assert!(keys(playground.get_pressed_keys()).eq("sdf".chars().map(KeyCode::Char)));
+    }
+
+    #[test]
+    fn run_stops_once_the_text_is_typed() {
+        let mut playground = TypingPlayground::new("ab cd".to_string(), TypingOptions::default());
+        let mut input = ScriptedEvents::new().type_text("ab cd").input();
+
+        let result = playground.run(&mut test_terminal(24, 3), &mut input);
+
+        assert!(result.is_ok());
+        assert_eq!(playground.get_user_events().len(), 5);
+    }
+
+    #[test]
+    fn fail_conditions_follow_the_clock_of_the_input() {
+        let mut playground = TypingPlayground::new("ab cd".to_string(), TypingOptions::default())
+            .with_fail_conditions(FailConditions {
+                min_wpm: Some(10.0),
+                ..Default::default()
+            });
+        let mut input = ScriptedEvents::new()
+            .type_text("ab")
+            .wait(Duration::from_secs(60))
+            .type_text(" ")
+            .input();
+
+        let result = playground.run(&mut test_terminal(24, 3), &mut input);
+
+        assert!(result.is_ok());
+        assert_eq!(playground.get_fail_reason(), Some(FailReason::LowWpm(10.0)));
+    }
 }